@@ -0,0 +1,41 @@
+use axum::extract::Query;
+use axum::routing::get;
+use axum::Router;
+
+markup::define! {
+    Page<'a>(name: &'a str) {
+        @markup::doctype()
+        html {
+            head {
+                title { "Hello, " @name }
+            }
+            body {
+                h1 { "Hello, " @name "!" }
+                form {
+                    input[name = "name", value = name];
+                    button { "Greet" }
+                }
+            }
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct Params {
+    name: Option<String>,
+}
+
+async fn index(Query(params): Query<Params>) -> markup::Html<impl markup::Render> {
+    let name = params.name.unwrap_or_else(|| String::from("World"));
+    markup::Html(markup::new!(@Page { name: &name }))
+}
+
+#[tokio::main]
+async fn main() {
+    let app = Router::new().route("/", get(index));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:3000")
+        .await
+        .unwrap();
+    println!("Listening on http://127.0.0.1:3000");
+    axum::serve(listener, app).await.unwrap();
+}
@@ -0,0 +1,73 @@
+use axum::http::{header, Request, StatusCode};
+use axum::routing::get;
+use axum::Router;
+use tower::ServiceExt;
+
+markup::define! {
+    Greeting<'a>(name: &'a str) {
+        h1 { "Hello, " @name "!" }
+    }
+}
+
+#[derive(Debug)]
+struct SecretError;
+
+impl std::fmt::Display for SecretError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("database password is hunter2")
+    }
+}
+
+impl std::error::Error for SecretError {}
+
+fn app() -> Router {
+    Router::new()
+        .route(
+            "/",
+            get(|| async { markup::Html(Greeting { name: "<Ferris>" }) }),
+        )
+        .route(
+            "/error",
+            get(|| async {
+                markup::Html(markup::new! {
+                    p { @Err::<&str, _>(SecretError)? }
+                })
+            }),
+        )
+}
+
+async fn get_body(uri: &str) -> (StatusCode, Option<String>, String) {
+    let response = app()
+        .oneshot(Request::get(uri).body(axum::body::Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|value| value.to_str().unwrap().to_string());
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (
+        status,
+        content_type,
+        String::from_utf8(body.to_vec()).unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn html() {
+    let (status, content_type, body) = get_body("/").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("text/html; charset=utf-8"));
+    assert_eq!(body, "<h1>Hello, &lt;Ferris&gt;!</h1>");
+}
+
+#[tokio::test]
+async fn error() {
+    let (status, content_type, body) = get_body("/error").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(content_type, None);
+    assert_eq!(body, "");
+}
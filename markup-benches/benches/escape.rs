@@ -1,11 +1,14 @@
 use criterion::{criterion_group, criterion_main, Criterion, Throughput};
 
-criterion_group!(benches, bench_escape, bench_escape_noop);
+criterion_group!(
+    benches,
+    bench_escape,
+    bench_escape_noop,
+    bench_escape_dense,
+    bench_escape_short
+);
 criterion_main!(benches);
 
-#[path = "../../markup/src/escape.rs"]
-mod escape;
-
 fn bench_escape(c: &mut Criterion) {
     let str = include_str!("./escape.html");
     let mut group = c.benchmark_group("escape");
@@ -29,8 +32,43 @@ fn bench_escape_noop(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_escape_dense(c: &mut Criterion) {
+    let string = r#"<a href="?a=1&b=2">"&"</a>"#.repeat(100);
+
+    let mut group = c.benchmark_group("escape_dense");
+    group.throughput(Throughput::Bytes(string.len() as u64));
+    group.bench_function("escape_dense", |b| b.iter(|| escape(&string)));
+    group.finish();
+}
+
+// Most strings rendered by templates are short field values.
+fn bench_escape_short(c: &mut Criterion) {
+    let strings = [
+        "",
+        "a",
+        "Jane Doe",
+        "a < b",
+        "tom&jerry",
+        "2023-01-01T00:00:00Z",
+    ];
+    let len = strings.iter().map(|str| str.len()).sum::<usize>();
+
+    let mut group = c.benchmark_group("escape_short");
+    group.throughput(Throughput::Bytes(len as u64));
+    group.bench_function("escape_short", |b| {
+        b.iter(|| {
+            let mut string = String::with_capacity(64);
+            for str in strings {
+                markup::escape::escape(str, &mut string).unwrap();
+            }
+            string
+        })
+    });
+    group.finish();
+}
+
 fn escape(str: &str) -> String {
     let mut string = String::new();
-    escape::escape(str, &mut string).unwrap();
+    markup::escape::escape(str, &mut string).unwrap();
     string
 }
@@ -2,11 +2,21 @@
 pub struct Struct {
     pub name: syn::Ident,
     pub attributes: Vec<syn::Attribute>,
+    pub options: Options,
     pub generics: syn::Generics,
     pub where_clause: Option<syn::WhereClause>,
     pub fields: Vec<syn::Field>,
+    pub rest: Option<syn::Ident>,
     pub children: Vec<Node>,
-    pub size_hint: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub dyn_write: bool,
+    pub a11y: bool,
+    pub trim: bool,
+    pub xml: bool,
+    pub legacy_escaping: bool,
 }
 
 #[derive(Debug)]
@@ -14,6 +24,19 @@ pub struct Template {
     pub children: Vec<Node>,
 }
 
+#[derive(Debug)]
+pub struct Render {
+    pub writer: syn::Expr,
+    pub template: Template,
+}
+
+#[derive(Debug)]
+pub struct RenderImpl {
+    pub generics: syn::Generics,
+    pub ty: syn::Type,
+    pub children: Vec<Node>,
+}
+
 #[derive(Debug)]
 pub enum Node {
     Element(Element),
@@ -22,6 +45,11 @@ pub enum Node {
     Expr(syn::Expr),
     Stmt(syn::Stmt),
     Match(Match),
+    Raw(syn::Expr),
+    Cache(Cache),
+    Cfg(Cfg),
+    Fragment(Fragment),
+    Pre(syn::LitStr),
 }
 
 #[derive(Debug)]
@@ -47,6 +75,7 @@ pub struct IfClause {
 }
 
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum IfClauseTest {
     Expr(syn::Expr),
     Let(syn::Pat, syn::Expr),
@@ -65,14 +94,36 @@ pub struct MatchClause {
     pub consequent: Vec<Node>,
 }
 
+#[derive(Debug)]
+pub struct Cfg {
+    pub predicate: proc_macro2::TokenStream,
+    pub body: Vec<Node>,
+    pub default: Option<Vec<Node>>,
+}
+
+#[derive(Debug)]
+pub struct Fragment {
+    pub name: syn::Ident,
+    pub body: Vec<Node>,
+}
+
+#[derive(Debug)]
+pub struct Cache {
+    pub key: syn::Expr,
+    pub cache: syn::Expr,
+    pub body: Vec<Node>,
+}
+
 #[derive(Debug)]
 pub struct For {
     pub pat: syn::Pat,
+    pub fallible: bool,
     pub expr: syn::Expr,
     pub body: Vec<Node>,
 }
 
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum Attribute {
     One(syn::Expr, syn::Expr),
     Many(syn::Expr),
@@ -1,4 +1,15 @@
 pub fn escape(str: &str, writer: &mut impl std::fmt::Write) -> std::fmt::Result {
+    escape_bytes::<_, false>(str, writer)
+}
+
+pub fn escape_attribute(str: &str, writer: &mut impl std::fmt::Write) -> std::fmt::Result {
+    escape_bytes::<_, true>(str, writer)
+}
+
+fn escape_bytes<W: std::fmt::Write, const APOSTROPHE: bool>(
+    str: &str,
+    writer: &mut W,
+) -> std::fmt::Result {
     let mut last = 0;
     for (index, byte) in str.bytes().enumerate() {
         macro_rules! go {
@@ -14,12 +25,14 @@ pub fn escape(str: &str, writer: &mut impl std::fmt::Write) -> std::fmt::Result
             b'<' => go!("&lt;"),
             b'>' => go!("&gt;"),
             b'"' => go!("&quot;"),
+            b'\'' if APOSTROPHE => go!("&#39;"),
             _ => {}
         }
     }
     writer.write_str(&str[last..])
 }
 
+#[allow(dead_code)]
 pub struct Escape<'a, W>(pub &'a mut W);
 
 impl<W: std::fmt::Write> std::fmt::Write for Escape<'_, W> {
@@ -56,6 +69,18 @@ fn test() {
     }
 }
 
+#[test]
+fn test_attribute() {
+    t("<'>", "&lt;&#39;&gt;");
+    t(r#"a'"&b"#, "a&#39;&quot;&amp;b");
+
+    fn t(input: &str, output: &str) {
+        let mut string = String::new();
+        escape_attribute(input, &mut string).unwrap();
+        assert_eq!(string, output);
+    }
+}
+
 #[test]
 fn test_arguments() {
     use std::fmt::Write;
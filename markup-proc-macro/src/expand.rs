@@ -0,0 +1,74 @@
+use proc_macro2::TokenStream;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const VAR: &str = "MARKUP_DEBUG_EXPAND";
+
+// With `MARKUP_DEBUG_EXPAND` set, writes the pretty printed expansion of a template to
+// `target/markup-expand/<Name>.rs`. The variable can be set to a comma separated list of names to
+// only dump those templates; `1` or an empty value dumps all of them. This only reads the tokens,
+// so the expansion itself is never affected. Stable proc macros can't emit notes, so the path is
+// printed to stderr instead.
+pub fn define(name: &syn::Ident, tokens: &TokenStream) {
+    dump(
+        &name.to_string(),
+        || syn::parse2::<syn::File>(tokens.clone()),
+        tokens,
+    );
+}
+
+// `new!` templates have no name, so they are numbered in the order they are expanded.
+pub fn new(tokens: &TokenStream) {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    if std::env::var_os(VAR).is_none() {
+        return;
+    }
+    let name = format!("new-{}", COUNT.fetch_add(1, Ordering::Relaxed) + 1);
+    dump(
+        &name,
+        || syn::parse2::<syn::File>(quote::quote!(fn new() { let _ = #tokens; })),
+        tokens,
+    );
+}
+
+fn dump(name: &str, file: impl FnOnce() -> syn::Result<syn::File>, tokens: &TokenStream) {
+    let filter = match std::env::var(VAR) {
+        Ok(filter) => filter,
+        Err(_) => return,
+    };
+    let filter = filter.trim();
+    if !filter.is_empty() && filter != "1" && !filter.split(',').any(|part| part.trim() == name) {
+        return;
+    }
+    let source = match file() {
+        Ok(file) => prettyplease::unparse(&file),
+        Err(_) => tokens.to_string(),
+    };
+    let dir = target_dir().join("markup-expand");
+    let path = dir.join(format!("{}.rs", name));
+    match std::fs::create_dir_all(&dir).and_then(|()| std::fs::write(&path, source)) {
+        Ok(()) => eprintln!(
+            "note: markup: wrote the expansion of `{}` to {}",
+            name,
+            path.display()
+        ),
+        Err(error) => eprintln!(
+            "warning: markup: couldn't write the expansion of `{}` to {}: {}",
+            name,
+            path.display(),
+            error
+        ),
+    }
+}
+
+fn target_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("CARGO_TARGET_DIR") {
+        return dir.into();
+    }
+    let manifest_dir = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default());
+    manifest_dir
+        .ancestors()
+        .map(|dir| dir.join("target"))
+        .find(|dir| dir.is_dir())
+        .unwrap_or_else(|| manifest_dir.join("target"))
+}
@@ -0,0 +1,58 @@
+use crate::ast::Struct;
+use crate::parse::Many;
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::path::PathBuf;
+
+// Reads `define!` input from a file relative to `CARGO_MANIFEST_DIR`. The generated
+// `include_str!` makes cargo recompile the crate when the file changes.
+pub fn define(path: &syn::LitStr) -> syn::Result<(Vec<Struct>, TokenStream)> {
+    let manifest_dir = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default());
+    let full_path = manifest_dir.join(path.value());
+    let source = std::fs::read_to_string(&full_path).map_err(|error| {
+        syn::Error::new(
+            path.span(),
+            format!("couldn't read {}: {}", full_path.display(), error),
+        )
+    })?;
+    let structs =
+        parse(&source, &path.value()).map_err(|error| syn::Error::new(path.span(), error))?;
+    let full_path = full_path.to_string_lossy();
+    Ok((
+        structs,
+        quote!(
+            const _: &str = include_str!(#full_path);
+        ),
+    ))
+}
+
+fn parse(source: &str, path: &str) -> Result<Vec<Struct>, String> {
+    match syn::parse_str::<Many<Struct>>(source) {
+        Ok(structs) => Ok(structs.0),
+        Err(error) => Err(format!(
+            "{}:{}",
+            path,
+            locate(source).unwrap_or_else(|| format!(" {}", error))
+        )),
+    }
+}
+
+// Tokens parsed from a string inside a proc macro have no location, so the source is parsed again
+// with proc-macro2's own implementation, which tracks lines and columns.
+fn locate(source: &str) -> Option<String> {
+    proc_macro2::fallback::force();
+    let result = syn::parse_str::<Many<Struct>>(source);
+    proc_macro2::fallback::unforce();
+    let error = result.err()?;
+    let start = error.span().start();
+    Some(format!("{}:{}: {}", start.line, start.column + 1, error))
+}
+
+#[test]
+fn test_locate() {
+    let source = "Article(title: &'static str) {\n    h1 { @title }\n    p { \"a\" ] }\n}\n";
+    assert!(matches!(
+        parse(source, "article.markup").err().as_deref(),
+        Some(error) if error.starts_with("article.markup:3:13: ")
+    ));
+}
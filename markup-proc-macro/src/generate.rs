@@ -1,5 +1,6 @@
 use crate::ast::{
-    Attribute, Element, For, If, IfClause, IfClauseTest, Match, MatchClause, Node, Struct, Template,
+    Attribute, Cache, Cfg, Element, For, Fragment, If, IfClause, IfClauseTest, Match, MatchClause,
+    Node, Options, Render, RenderImpl, Struct, Template,
 };
 use proc_macro2::TokenStream;
 use proc_macro2::TokenTree;
@@ -10,15 +11,46 @@ impl ToTokens for Struct {
         let Struct {
             name,
             attributes,
+            options,
             generics,
             where_clause,
             fields,
+            rest,
             children,
-            size_hint,
         } = self;
-        let mut stream = Stream::default();
+        let mut diagnostics = crate::lint::check(children);
+        if options.a11y {
+            crate::lint::a11y(children, false, &mut diagnostics);
+        }
+        let mut stream = Stream::new(options);
+        #[cfg(feature = "tracing")]
+        stream.extend(quote! {
+            let __span = ::markup::tracing::debug_span!("markup::render", template = stringify!(#name));
+            let __entered = __span.enter();
+        });
+        #[cfg(feature = "dev-reload")]
+        stream.extend(dev_reload(name, fields));
+        #[cfg(feature = "debug-comments")]
+        stream.extend(quote!(::markup::debug_comments::start(__writer, stringify!(#name))?;));
         children.generate(&mut stream);
+        #[cfg(feature = "debug-comments")]
+        stream.extend(quote!(::markup::debug_comments::end(__writer, stringify!(#name))?;));
+        let size_hint = stream.static_len();
         let built = stream.finish();
+        let mut fragments = Vec::new();
+        self::fragments(children, &TokenStream::new(), &mut fragments);
+        let mut names = Vec::new();
+        fragments.retain(|(fragment, _)| {
+            if names.contains(&&fragment.name) {
+                diagnostics.error(
+                    fragment.name.span(),
+                    format!("duplicate @fragment `{}`", fragment.name),
+                );
+                return false;
+            }
+            names.push(&fragment.name);
+            true
+        });
         let (impl_generics, ty_generics, _) = generics.split_for_impl();
         let mut struct_fields = TokenStream::new();
         let mut splat_fields = TokenStream::new();
@@ -30,11 +62,87 @@ impl ToTokens for Struct {
                 #(#attrs)*
                 pub #name: #ty,
             });
+            let cfgs = attrs.iter().filter(|attr| attr.path().is_ident("cfg"));
             splat_fields.extend(quote! {
+                #(#cfgs)*
                 #name,
             });
         }
+        // The field has a fixed name so that call sites can fill it in without knowing the
+        // name it is bound to in the template.
+        if let Some(rest) = rest {
+            struct_fields.extend(quote! {
+                #[doc(hidden)]
+                pub __markup_rest: ::markup::AttrMap,
+            });
+            splat_fields.extend(quote!(__markup_rest: #rest,));
+        }
+        // With `dyn_write`, the body is compiled once against `&mut dyn Write` instead of once per
+        // writer type.
+        let render = if options.dyn_write {
+            quote! {
+                impl #impl_generics #name #ty_generics #where_clause {
+                    #[doc(hidden)]
+                    pub fn __markup_render_dyn(
+                        &self,
+                        mut __writer: &mut dyn std::fmt::Write,
+                    ) -> Result<(), ::markup::RenderError> {
+                        let __writer = &mut __writer;
+                        let #name { #splat_fields } = self;
+                        #built
+                        Ok(())
+                    }
+                }
+                impl #impl_generics ::markup::Render for #name #ty_generics #where_clause {
+                    #[inline]
+                    fn render(&self, __writer: &mut impl std::fmt::Write) -> Result<(), ::markup::RenderError> {
+                        self.__markup_render_dyn(__writer)
+                    }
+
+                    #[inline]
+                    fn size_hint(&self) -> usize {
+                        #size_hint
+                    }
+                }
+            }
+        } else {
+            quote! {
+                impl #impl_generics ::markup::Render for #name #ty_generics #where_clause {
+                    fn render(&self, __writer: &mut impl std::fmt::Write) -> Result<(), ::markup::RenderError> {
+                        let #name { #splat_fields } = self;
+                        #built
+                        Ok(())
+                    }
+
+                    #[inline]
+                    fn size_hint(&self) -> usize {
+                        #size_hint
+                    }
+                }
+            }
+        };
+        let fragments = fragments.iter().map(|(Fragment { name: fragment, body }, cfgs)| {
+            let method = quote::format_ident!("render_{}", fragment);
+            let mut stream = Stream::new(options);
+            body.generate(&mut stream);
+            let built = stream.finish();
+            let doc = format!("Renders only `@fragment {}`.", fragment);
+            quote! {
+                #[doc = #doc]
+                #cfgs
+                #[allow(unused_variables)]
+                pub fn #method(&self, __writer: &mut impl std::fmt::Write) -> Result<(), ::markup::RenderError> {
+                    let #name { #splat_fields } = self;
+                    #built
+                    Ok(())
+                }
+            }
+        });
+        let fragments = quote! {
+            #(#fragments)*
+        };
         tokens.extend(quote! {
+            #diagnostics
             #(#attributes)*
             pub struct #name #generics #where_clause {
                 #struct_fields
@@ -43,36 +151,117 @@ impl ToTokens for Struct {
                 /// An inlined version of to_string which uses [`markup::Render`]
                 #[inline]
                 pub fn to_string(&self) -> String {
-                    let mut string = String::with_capacity(#size_hint);
+                    let mut string = String::with_capacity(::markup::Render::size_hint(self));
                     // Ignoring the result because writing to a String can't fail.
                     let _ = ::markup::Render::render(self, &mut string);
                     string
                 }
+
+                #fragments
             }
-            impl #impl_generics ::markup::Render for #name #ty_generics #where_clause {
-                fn render(&self, __writer: &mut impl std::fmt::Write) -> std::fmt::Result {
-                    let #name { #splat_fields } = self;
-                    #built
-                    Ok(())
-                }
-            }
+            #render
             impl #impl_generics std::fmt::Display for #name #ty_generics #where_clause {
                 #[inline]
                 fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
-                    ::markup::Render::render(self, fmt)
+                    ::markup::Render::render(self, fmt).map_err(|_| std::fmt::Error)
                 }
             }
         })
     }
 }
 
+// Renders the override file of the template instead, if there is one. Fields which don't
+// implement `Render` are looked up as `None`, using autoref specialization.
+#[cfg(feature = "dev-reload")]
+fn dev_reload(name: &syn::Ident, fields: &[syn::Field]) -> TokenStream {
+    let pushes = fields.iter().map(|field| {
+        let cfgs = field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("cfg"));
+        let name = field.ident.as_ref().unwrap();
+        quote! {
+            #(#cfgs)*
+            __fields.push((
+                stringify!(#name),
+                (&::markup::dev_reload::Field(&self.#name)).lookup(),
+            ));
+        }
+    });
+    quote! {
+        #[cfg(debug_assertions)]
+        {
+            if let ::std::option::Option::Some(__override) =
+                ::markup::dev_reload::load(stringify!(#name), file!())
+            {
+                #[allow(unused_imports)]
+                use ::markup::dev_reload::{ViaFallback as _, ViaRender as _};
+                #[allow(unused_mut)]
+                let mut __fields: ::std::vec::Vec<(
+                    &'static str,
+                    ::std::option::Option<&dyn ::markup::RenderDyn>,
+                )> = ::std::vec::Vec::new();
+                #(#pushes)*
+                if let ::std::option::Option::Some(__result) = __override.render(&__fields, __writer) {
+                    return __result;
+                }
+            }
+        }
+    }
+}
+
+// The fragments of a template, including nested ones, in order, with the `#[cfg]` attributes of
+// the `@cfg` blocks they are in.
+fn fragments<'a>(
+    nodes: &'a [Node],
+    cfgs: &TokenStream,
+    fragments: &mut Vec<(&'a Fragment, TokenStream)>,
+) {
+    for node in nodes {
+        match node {
+            Node::Element(element) => self::fragments(&element.children, cfgs, fragments),
+            Node::If(if_) => {
+                for clause in &if_.clauses {
+                    self::fragments(&clause.consequent, cfgs, fragments);
+                }
+                self::fragments(if_.default.as_deref().unwrap_or_default(), cfgs, fragments);
+            }
+            Node::Match(match_) => {
+                for clause in &match_.clauses {
+                    self::fragments(&clause.consequent, cfgs, fragments);
+                }
+            }
+            Node::For(for_) => self::fragments(&for_.body, cfgs, fragments),
+            Node::Cache(cache) => self::fragments(&cache.body, cfgs, fragments),
+            Node::Cfg(cfg) => {
+                let predicate = &cfg.predicate;
+                let body = quote!(#cfgs #[cfg(#predicate)]);
+                self::fragments(&cfg.body, &body, fragments);
+                let default = quote!(#cfgs #[cfg(not(#predicate))]);
+                self::fragments(
+                    cfg.default.as_deref().unwrap_or_default(),
+                    &default,
+                    fragments,
+                );
+            }
+            Node::Fragment(fragment) => {
+                fragments.push((fragment, cfgs.clone()));
+                self::fragments(&fragment.body, cfgs, fragments);
+            }
+            Node::Expr(_) | Node::Stmt(_) | Node::Raw(_) | Node::Pre(_) => {}
+        }
+    }
+}
+
 impl ToTokens for Template {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let Self { children } = self;
+        let diagnostics = crate::lint::check(children);
         let mut stream = Stream::default();
         children.generate(&mut stream);
         let built = stream.finish();
         tokens.extend(quote! {{
+            #diagnostics
             ::markup::new(move |mut __writer| {
                 let mut __writer = &mut __writer;
                 #built
@@ -82,6 +271,49 @@ impl ToTokens for Template {
     }
 }
 
+impl ToTokens for Render {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Self { writer, template } = self;
+        let children = &template.children;
+        let diagnostics = crate::lint::check(children);
+        let mut stream = Stream::default();
+        children.generate(&mut stream);
+        let built = stream.finish();
+        tokens.extend(quote! {{
+            #diagnostics
+            let __writer = &mut (#writer);
+            (|| -> Result<(), ::markup::RenderError> {
+                #built
+                Ok(())
+            })()
+        }})
+    }
+}
+
+impl ToTokens for RenderImpl {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Self {
+            generics,
+            ty,
+            children,
+        } = self;
+        let diagnostics = crate::lint::check(children);
+        let mut stream = Stream::default();
+        children.generate(&mut stream);
+        let built = stream.finish();
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        tokens.extend(quote! {
+            #diagnostics
+            impl #impl_generics ::markup::Render for #ty #where_clause {
+                fn render(&self, __writer: &mut impl std::fmt::Write) -> Result<(), ::markup::RenderError> {
+                    #built
+                    Ok(())
+                }
+            }
+        })
+    }
+}
+
 trait Generate {
     fn generate(&self, stream: &mut Stream);
 }
@@ -101,8 +333,20 @@ impl Generate for Node {
             Node::If(if_) => if_.generate(stream),
             Node::Match(match_) => match_.generate(stream),
             Node::For(for_) => for_.generate(stream),
+            Node::Cache(cache) => cache.generate(stream),
+            Node::Cfg(cfg) => cfg.generate(stream),
+            Node::Fragment(fragment) => stream.braced(|stream| fragment.body.generate(stream)),
+            Node::Pre(lit_str) => stream.escaped(&lit_str.value()),
             Node::Expr(expr) => stream.expr(expr),
             Node::Stmt(stmt) => stream.extend(stmt.into_token_stream()),
+            Node::Raw(syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(lit_str),
+                ..
+            })) => stream.raw(&lit_str.value()),
+            Node::Raw(expr) => {
+                let expr = unwrap_try(expr);
+                stream.extend(quote!(::markup::Render::render(&::markup::raw(#expr), __writer)?;))
+            }
         }
     }
 }
@@ -118,10 +362,10 @@ impl Generate for Element {
             close,
         } = self;
         stream.raw("<");
-        stream.expr(name);
+        stream.tag_expr(name);
         if let Some(id) = id {
             stream.raw(" id=\"");
-            stream.expr(id);
+            stream.attr_value(id);
             stream.raw("\"");
         }
         if !classes.is_empty() {
@@ -133,13 +377,44 @@ impl Generate for Element {
                 } else {
                     stream.raw(" ");
                 }
-                stream.expr(class);
+                stream.attr_value(class);
             }
             stream.raw("\"");
         }
 
         fn attr(stream: &mut Stream, name: &syn::Expr, value: &syn::Expr) {
+            // Literals are rendered during expansion. XML has no boolean attributes, so `true` and
+            // `false` are written as values there.
+            match fold(value) {
+                Some(Folded::Bool(false)) if !stream.xml => return,
+                Some(Folded::Bool(true)) if !stream.xml => {
+                    stream.raw(" ");
+                    stream.tag_expr(name);
+                    return;
+                }
+                Some(folded) => {
+                    stream.raw(" ");
+                    stream.tag_expr(name);
+                    stream.raw("=\"");
+                    stream.attr_folded(folded);
+                    stream.raw("\"");
+                    return;
+                }
+                None => {}
+            }
+            let value = unwrap_try(value);
             stream.extend(quote!(let __value = #value;));
+            if stream.xml {
+                stream.extend(quote!(if !::markup::RenderAttributeValue::is_none(&__value)));
+                stream.braced(|stream| {
+                    stream.raw(" ");
+                    stream.tag_expr(name);
+                    stream.raw("=\"");
+                    stream.attr_value(&syn::parse_quote!(__value));
+                    stream.raw("\"");
+                });
+                return;
+            }
             stream.extend(quote!(
             if ::markup::RenderAttributeValue::is_none(&__value) ||
                ::markup::RenderAttributeValue::is_false(&__value)
@@ -148,19 +423,23 @@ impl Generate for Element {
             stream.extend(quote!(else if ::markup::RenderAttributeValue::is_true(&__value)));
             stream.braced(|stream| {
                 stream.raw(" ");
-                stream.expr(name);
+                stream.tag_expr(name);
             });
             stream.extend(quote!(else));
             stream.braced(|stream| {
                 stream.raw(" ");
-                stream.expr(name);
+                stream.tag_expr(name);
                 stream.raw("=\"");
-                stream.expr(&syn::parse_quote!(__value));
+                stream.attr_value(&syn::parse_quote!(__value));
                 stream.raw("\"");
             });
         }
 
-        for attribute in attributes {
+        // Literal attributes come first, then spreads.
+        let (literal, spread): (Vec<_>, Vec<_>) = attributes
+            .iter()
+            .partition(|attribute| matches!(attribute, Attribute::One(..)));
+        for attribute in literal.into_iter().chain(spread) {
             match attribute {
                 Attribute::One(name, value) => attr(stream, name, value),
                 Attribute::Many(iter) => {
@@ -168,7 +447,7 @@ impl Generate for Element {
                     stream.braced(|stream| {
                         attr(
                             stream,
-                            &syn::parse_quote!(__name),
+                            &syn::parse_quote!(::markup::SpreadName(&__name)),
                             &syn::parse_quote!(__value),
                         );
                     });
@@ -176,9 +455,23 @@ impl Generate for Element {
             }
         }
 
+        // XML elements without children are closed in the start tag.
+        if stream.xml && children.is_empty() {
+            stream.raw("/>");
+            return;
+        }
+
         stream.raw(">");
 
-        children.generate(stream);
+        match name {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(lit_str),
+                ..
+            }) if RAW_TEXT_ELEMENTS.contains(&&*lit_str.value()) => {
+                stream.without_debug_comments(|stream| children.generate(stream))
+            }
+            _ => children.generate(stream),
+        }
 
         if *close {
             stream.raw("</");
@@ -239,9 +532,93 @@ impl Generate for Match {
 
 impl Generate for For {
     fn generate(&self, stream: &mut Stream) {
-        let For { pat, expr, body } = self;
-        stream.extend(quote!(for #pat in #expr));
-        stream.braced(|stream| body.generate(stream))
+        let For {
+            pat,
+            fallible,
+            expr,
+            body,
+        } = self;
+        if *fallible {
+            let item = propagate(quote!(__item));
+            stream.extend(quote!(for __item in #expr));
+            stream.braced(|stream| {
+                stream.extend(quote!(let #pat = #item;));
+                body.generate(stream)
+            })
+        } else {
+            stream.extend(quote!(for #pat in #expr));
+            stream.braced(|stream| body.generate(stream))
+        }
+    }
+}
+
+// Elements whose contents are not parsed as HTML, so comments would show up as text or code.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "textarea", "title"];
+
+// A trailing `?` on an expression propagates the error through `markup::RenderError::wrap`, so
+// any error type works without a `From` impl.
+fn unwrap_try(expr: &syn::Expr) -> TokenStream {
+    match expr {
+        syn::Expr::Try(syn::ExprTry { expr, .. }) => propagate(unwrap_try(expr)),
+        _ => expr.into_token_stream(),
+    }
+}
+
+fn propagate(result: TokenStream) -> TokenStream {
+    quote! {
+        match #result {
+            ::std::result::Result::Ok(__value) => __value,
+            ::std::result::Result::Err(__error) => {
+                return ::std::result::Result::Err(::markup::RenderError::wrap(__error));
+            }
+        }
+    }
+}
+
+impl Generate for Cache {
+    fn generate(&self, stream: &mut Stream) {
+        let Cache { key, cache, body } = self;
+        stream.braced(|stream| {
+            stream.extend(quote! {
+                let __cache = &(#cache);
+                let __key = #key;
+                if let ::std::option::Option::Some(__fragment) =
+                    ::markup::cache::Cache::get(__cache, &__key)
+                {
+                    ::std::fmt::Write::write_str(__writer, &__fragment)?;
+                }
+            });
+            stream.extend(quote!(else));
+            stream.braced(|stream| {
+                stream.extend(quote!(let mut __fragment = ::std::string::String::new();));
+                // An error returns early from the block, so nothing is stored in the cache.
+                stream.braced(|stream| {
+                    stream.extend(quote!(let __writer = &mut __fragment;));
+                    body.generate(stream);
+                });
+                stream.extend(quote! {
+                    ::std::fmt::Write::write_str(__writer, &__fragment)?;
+                    ::markup::cache::Cache::insert(__cache, __key, __fragment);
+                });
+            });
+        });
+    }
+}
+
+// Each branch is a block statement under `#[cfg]`, so the inactive one is never type checked.
+impl Generate for Cfg {
+    fn generate(&self, stream: &mut Stream) {
+        let Cfg {
+            predicate,
+            body,
+            default,
+        } = self;
+        stream.extend(quote!(#[cfg(#predicate)]));
+        stream.braced(|stream| body.generate(stream));
+        if let Some(default) = default {
+            stream.extend(quote!(#[cfg(not(#predicate))]));
+            stream.braced(|stream| default.generate(stream));
+        }
     }
 }
 
@@ -249,9 +626,98 @@ impl Generate for For {
 struct Stream {
     stream: TokenStream,
     buffer: String,
+    // The length of the static output emitted so far, including nested blocks.
+    len: usize,
+    // Set by `#[markup(xml)]`.
+    xml: bool,
+    // Set by `#[markup(legacy_escaping)]`.
+    legacy_escaping: bool,
+}
+
+// The output of a literal, as its `Render` impl would write it. Numbers and chars aren't escaped
+// at runtime either.
+enum Folded {
+    Str(String),
+    Raw(String),
+    Bool(bool),
+}
+
+fn fold(expr: &syn::Expr) -> Option<Folded> {
+    let (negative, lit) = match expr {
+        syn::Expr::Lit(syn::ExprLit { lit, .. }) => (false, lit),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => match &**expr {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: lit @ (syn::Lit::Int(_) | syn::Lit::Float(_)),
+                ..
+            }) => (true, lit),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    match lit {
+        syn::Lit::Str(lit_str) => Some(Folded::Str(lit_str.value())),
+        syn::Lit::Char(lit_char) => Some(Folded::Raw(lit_char.value().to_string())),
+        syn::Lit::Bool(lit_bool) => Some(Folded::Bool(lit_bool.value)),
+        syn::Lit::Int(lit_int) => match lit_int.suffix() {
+            "f32" | "f64" => float(lit_int.base10_digits(), lit_int.suffix(), negative),
+            suffix => int(lit_int.base10_parse().ok()?, suffix, negative),
+        },
+        syn::Lit::Float(lit_float) => {
+            float(lit_float.base10_digits(), lit_float.suffix(), negative)
+        }
+        _ => None,
+    }
+}
+
+// Only folds values within the range of their type, so out of range literals still fail to
+// compile.
+fn int(value: u128, suffix: &str, negative: bool) -> Option<Folded> {
+    let (min, max): (u128, u128) = match suffix {
+        "" | "i32" => (1 << 31, i32::MAX as u128),
+        "i8" => (1 << 7, i8::MAX as u128),
+        "i16" => (1 << 15, i16::MAX as u128),
+        "i64" | "isize" => (1 << 63, i64::MAX as u128),
+        "i128" => (1 << 127, i128::MAX as u128),
+        "u8" => (0, u8::MAX as u128),
+        "u16" => (0, u16::MAX as u128),
+        "u32" => (0, u32::MAX as u128),
+        "u64" | "usize" => (0, u64::MAX as u128),
+        "u128" => (0, u128::MAX),
+        _ => return None,
+    };
+    match (negative, value) {
+        (false, value) if value <= max => Some(Folded::Raw(value.to_string())),
+        (true, 0) if min > 0 => Some(Folded::Raw(String::from("0"))),
+        (true, value) if value <= min => Some(Folded::Raw(format!("-{}", value))),
+        _ => None,
+    }
+}
+
+fn float(digits: &str, suffix: &str, negative: bool) -> Option<Folded> {
+    fn signed<T: std::ops::Neg<Output = T> + ToString>(value: T, negative: bool) -> String {
+        if negative { -value } else { value }.to_string()
+    }
+    let string = match suffix {
+        "f32" => signed(digits.parse::<f32>().ok()?, negative),
+        "" | "f64" => signed(digits.parse::<f64>().ok()?, negative),
+        _ => return None,
+    };
+    Some(Folded::Raw(string))
 }
 
 impl Stream {
+    fn new(options: &Options) -> Self {
+        Stream {
+            xml: options.xml,
+            legacy_escaping: options.legacy_escaping,
+            ..Stream::default()
+        }
+    }
+
     fn raw(&mut self, str: &str) {
         self.buffer.push_str(str);
     }
@@ -263,31 +729,110 @@ impl Stream {
     }
 
     fn expr(&mut self, expr: &syn::Expr) {
-        match expr {
-            syn::Expr::Lit(syn::ExprLit {
-                lit: syn::Lit::Str(lit_str),
-                ..
-            }) => self.escaped(&lit_str.value()),
-            _ => self.extend(quote!(::markup::Render::render(&(#expr), __writer)?;)),
+        match fold(expr) {
+            Some(folded) => self.folded(folded),
+            None => {
+                let expr = unwrap_try(expr);
+                self.extend(quote!(::markup::Render::render(&(#expr), __writer)?;))
+            }
+        }
+    }
+
+    fn folded(&mut self, folded: Folded) {
+        match folded {
+            Folded::Str(str) => self.escaped(&str),
+            Folded::Raw(str) => self.raw(&str),
+            Folded::Bool(bool) => self.raw(if bool { "true" } else { "false" }),
+        }
+    }
+
+    // Expressions inside a start tag, where debug comments must not be written.
+    fn tag_expr(&mut self, expr: &syn::Expr) {
+        match fold(expr) {
+            Some(folded) => self.folded(folded),
+            None => self.without_debug_comments(|stream| stream.expr(expr)),
+        }
+    }
+
+    // Attribute values also have `'` escaped, unless `#[markup(legacy_escaping)]` is set outside
+    // of XML templates.
+    fn escape_apostrophes(&self) -> bool {
+        self.xml || !self.legacy_escaping
+    }
+
+    fn attr_value(&mut self, expr: &syn::Expr) {
+        if !self.escape_apostrophes() {
+            return self.tag_expr(expr);
+        }
+        match fold(expr) {
+            Some(folded) => self.attr_folded(folded),
+            None => self.without_debug_comments(|stream| {
+                let expr = unwrap_try(expr);
+                stream.extend(quote! {
+                    ::markup::Render::render(
+                        &(#expr),
+                        &mut ::markup::escape::Apostrophes(&mut *__writer),
+                    )?;
+                })
+            }),
         }
     }
 
+    fn attr_folded(&mut self, folded: Folded) {
+        match folded {
+            Folded::Str(str) if self.escape_apostrophes() => {
+                let mut string = String::new();
+                crate::escape::escape_attribute(&str, &mut string).unwrap();
+                self.buffer.push_str(&string);
+            }
+            folded => self.folded(folded),
+        }
+    }
+
+    #[cfg(feature = "debug-comments")]
+    fn without_debug_comments(&mut self, f: impl Fn(&mut Stream)) {
+        self.braced(|stream| {
+            stream.extend(quote!(let __guard = ::markup::debug_comments::Guard::new();));
+            f(stream);
+        })
+    }
+
+    #[cfg(not(feature = "debug-comments"))]
+    fn without_debug_comments(&mut self, f: impl Fn(&mut Stream)) {
+        f(self)
+    }
+
     fn extend<Iter: IntoIterator<Item = TokenTree>>(&mut self, iter: Iter) {
+        // Static output is emitted as plain string literals. rustc already merges identical
+        // literals across templates, so hoisting them into shared constants wouldn't make the
+        // binary any smaller, see `markup/tests/static_literals.rs`.
         if !self.buffer.is_empty() {
             let buffer = &self.buffer;
             self.stream.extend(quote! {
-                __writer.write_str(#buffer)?;
+                ::std::fmt::Write::write_str(__writer, #buffer)?;
             });
+            self.len += self.buffer.len();
             self.buffer.clear();
         }
         self.stream.extend(iter);
     }
 
     fn braced(&mut self, f: impl Fn(&mut Stream)) {
-        let mut stream = Stream::default();
+        let mut stream = Stream {
+            xml: self.xml,
+            legacy_escaping: self.legacy_escaping,
+            ..Stream::default()
+        };
         f(&mut stream);
+        self.len += stream.len + stream.buffer.len();
         let stream = stream.finish();
-        self.stream.extend(quote!({#stream}));
+        self.extend(quote!({#stream}));
+    }
+
+    // The sum of the lengths of the static output, used as the template's size hint. Static output
+    // inside branches and loops is counted once, whether or not it is rendered.
+    fn static_len(&self) -> usize {
+        self.len + self.buffer.len()
     }
 
     fn finish(mut self) -> TokenStream {
@@ -295,3 +840,61 @@ impl Stream {
         self.stream
     }
 }
+
+#[cfg(test)]
+fn expand(template: &str) -> String {
+    let template: Template = syn::parse_str(template).unwrap();
+    quote!(#template).to_string()
+}
+
+#[test]
+fn test_static_escaping() {
+    let expanded = expand(r#"a[href = "?a=1&b=\"2\"", title = "<- back"] { "Tom & Jerry <3" }"#);
+    assert!(!expanded.contains("Render"), "{}", expanded);
+    assert!(expanded.contains(
+        r#""<a href=\"?a=1&amp;b=&quot;2&quot;\" title=\"&lt;- back\">Tom &amp; Jerry &lt;3</a>""#
+    ));
+}
+
+#[test]
+fn test_literal_folding() {
+    let expanded = expand(
+        r#"td[maxlength = 80u8, min = -5, checked = true, hidden = false, step = 0.5] {
+            42 " " -7 " " 0x1F " " 1.5 " " -0.0 " " 1e3 " " 2.5f32 " " 3f64 " " true " " '<'
+        }"#,
+    );
+    assert!(!expanded.contains("Render"), "{}", expanded);
+    assert!(expanded.contains(
+        r#""<td maxlength=\"80\" min=\"-5\" checked step=\"0.5\">42 -7 31 1.5 -0 1000 2.5 3 true <</td>""#
+    ));
+    // Out of range literals are left to rustc.
+    assert!(expand("128i8").contains("Render"));
+    assert!(expand("-1u8").contains("Render"));
+    assert!(expand("2147483648").contains("Render"));
+}
+
+#[test]
+fn test_xml() {
+    let expand = |template: &str| {
+        let struct_: Struct = syn::parse_str(template).unwrap();
+        quote!(#struct_).to_string()
+    };
+    let expanded = expand(
+        r#"#[markup(xml)] Icon { svg[focusable = false, hidden = true, title = "Tom's"] { path; g {} } }"#,
+    );
+    assert!(
+        expanded.contains(
+            r#""<svg focusable=\"false\" hidden=\"true\" title=\"Tom&#39;s\"><path/><g/></svg>""#
+        ),
+        "{}",
+        expanded
+    );
+}
+
+#[test]
+fn test_cfg() {
+    let expanded =
+        expand(r#"p { "a" @cfg(feature = "admin") { b { "c" } "d" } @cfg_else { "e" } }"#);
+    assert!(expanded.contains(r#"# [cfg (feature = "admin")] { :: std :: fmt :: Write :: write_str (__writer , "<b>c</b>d") ? ; }"#), "{}", expanded);
+    assert!(expanded.contains(r#"# [cfg (not (feature = "admin"))] { :: std :: fmt :: Write :: write_str (__writer , "e") ? ; }"#), "{}", expanded);
+}
@@ -1,16 +1,57 @@
 mod ast;
 mod escape;
+mod expand;
+mod file;
 mod generate;
+mod lint;
 mod parse;
+mod trim;
 
 #[proc_macro]
 pub fn define(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let structs = syn::parse_macro_input!(tokens as parse::Many<ast::Struct>).0;
-    quote::quote!( #(#structs)* ).into()
+    define_structs(&structs).into()
+}
+
+#[proc_macro]
+pub fn define_from_file(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let path = syn::parse_macro_input!(tokens as syn::LitStr);
+    match file::define(&path) {
+        Ok((structs, include)) => {
+            let mut tokens = define_structs(&structs);
+            tokens.extend(include);
+            tokens.into()
+        }
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn define_structs(structs: &[ast::Struct]) -> proc_macro2::TokenStream {
+    let mut tokens = proc_macro2::TokenStream::new();
+    for struct_ in structs {
+        let expanded = quote::quote!( #struct_ );
+        expand::define(&struct_.name, &expanded);
+        tokens.extend(expanded);
+    }
+    tokens
 }
 
 #[proc_macro]
 pub fn new(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let new = syn::parse_macro_input!(tokens as ast::Template);
-    quote::quote!( #new ).into()
+    let tokens = quote::quote!( #new );
+    expand::new(&tokens);
+    tokens.into()
+}
+
+#[proc_macro]
+pub fn render_impl(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let render_impl = syn::parse_macro_input!(tokens as ast::RenderImpl);
+    quote::quote!( #render_impl ).into()
+}
+
+#[proc_macro]
+pub fn render(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let render = syn::parse_macro_input!(tokens as ast::Render);
+    quote::quote!( #render ).into()
 }
@@ -0,0 +1,308 @@
+use crate::ast::{Attribute, Element, IfClauseTest, Node};
+use proc_macro2::{Span, TokenStream};
+use quote::{quote_spanned, ToTokens};
+
+#[derive(Default)]
+pub struct Diagnostics {
+    error: Option<syn::Error>,
+    warnings: TokenStream,
+}
+
+impl Diagnostics {
+    pub fn error(&mut self, span: Span, message: impl std::fmt::Display) {
+        let error = syn::Error::new(span, message);
+        match &mut self.error {
+            Some(existing) => existing.combine(error),
+            None => self.error = Some(error),
+        }
+    }
+
+    // Stable proc macros can't emit warnings, so we reference a deprecated constant at the given
+    // span instead. The message shows up as a regular `deprecated` lint which can also be denied.
+    pub fn warning(&mut self, span: Span, message: impl std::fmt::Display) {
+        let message = message.to_string();
+        let name = syn::Ident::new("markup_warning", span);
+        self.warnings.extend(quote_spanned! {span=>
+            const _: () = {
+                #[deprecated(note = #message)]
+                #[allow(non_upper_case_globals)]
+                const #name: () = ();
+                #name
+            };
+        });
+    }
+}
+
+impl ToTokens for Diagnostics {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        if let Some(error) = &self.error {
+            tokens.extend(error.to_compile_error());
+        }
+        tokens.extend(self.warnings.clone());
+    }
+}
+
+pub fn check(children: &[Node]) -> Diagnostics {
+    let mut diagnostics = Diagnostics::default();
+    ids(children, &mut Vec::new(), false, &mut diagnostics);
+    fragments(children, None, &mut diagnostics);
+    diagnostics
+}
+
+// Only ids which are string literals are checked. Branches of `@if`, `@match` and `@cfg` are
+// mutually exclusive so they are checked independently of each other.
+fn ids<'a>(
+    nodes: &'a [Node],
+    seen: &mut Vec<&'a syn::LitStr>,
+    in_loop: bool,
+    diagnostics: &mut Diagnostics,
+) {
+    for node in nodes {
+        match node {
+            Node::Element(element) => {
+                for id in static_ids(element) {
+                    if let Some(first) = seen.iter().find(|seen| seen.value() == id.value()) {
+                        diagnostics.error(id.span(), format!("duplicate id `{}`", id.value()));
+                        diagnostics.error(
+                            first.span(),
+                            format!("id `{}` first used here", first.value()),
+                        );
+                    } else {
+                        if in_loop {
+                            diagnostics.warning(
+                                id.span(),
+                                format!(
+                                    "static id `{}` inside @for is rendered once per iteration",
+                                    id.value()
+                                ),
+                            );
+                        }
+                        seen.push(id);
+                    }
+                }
+                ids(&element.children, seen, in_loop, diagnostics);
+            }
+            Node::If(if_) => branches(
+                if_.clauses
+                    .iter()
+                    .map(|clause| &clause.consequent[..])
+                    .chain(if_.default.as_deref()),
+                seen,
+                in_loop,
+                diagnostics,
+            ),
+            Node::Match(match_) => branches(
+                match_.clauses.iter().map(|clause| &clause.consequent[..]),
+                seen,
+                in_loop,
+                diagnostics,
+            ),
+            Node::For(for_) => ids(&for_.body, seen, true, diagnostics),
+            Node::Cache(cache) => ids(&cache.body, seen, in_loop, diagnostics),
+            Node::Fragment(fragment) => ids(&fragment.body, seen, in_loop, diagnostics),
+            Node::Cfg(cfg) => branches(
+                std::iter::once(&cfg.body[..]).chain(cfg.default.as_deref()),
+                seen,
+                in_loop,
+                diagnostics,
+            ),
+            Node::Expr(_) | Node::Stmt(_) | Node::Raw(_) | Node::Pre(_) => {}
+        }
+    }
+}
+
+// `@fragment` methods only see the fields of the template, so fragments can't be placed where
+// the template binds variables of its own. `scope` describes the innermost such place, and the
+// scope after the nodes is returned since a `@let` inside an element stays in scope after it.
+fn fragments(
+    nodes: &[Node],
+    mut scope: Option<&'static str>,
+    diagnostics: &mut Diagnostics,
+) -> Option<&'static str> {
+    for node in nodes {
+        match node {
+            Node::Element(element) => scope = fragments(&element.children, scope, diagnostics),
+            Node::If(if_) => {
+                for clause in &if_.clauses {
+                    let scope = match clause.test {
+                        IfClauseTest::Let(..) => Some("inside @if let"),
+                        IfClauseTest::Expr(_) => scope,
+                    };
+                    fragments(&clause.consequent, scope, diagnostics);
+                }
+                fragments(
+                    if_.default.as_deref().unwrap_or_default(),
+                    scope,
+                    diagnostics,
+                );
+            }
+            Node::Match(match_) => {
+                for clause in &match_.clauses {
+                    fragments(&clause.consequent, Some("inside @match"), diagnostics);
+                }
+            }
+            Node::For(for_) => {
+                fragments(&for_.body, Some("inside @for"), diagnostics);
+            }
+            Node::Cache(cache) => {
+                fragments(&cache.body, Some("inside @cache"), diagnostics);
+            }
+            Node::Cfg(cfg) => {
+                fragments(&cfg.body, scope, diagnostics);
+                fragments(
+                    cfg.default.as_deref().unwrap_or_default(),
+                    scope,
+                    diagnostics,
+                );
+            }
+            Node::Fragment(fragment) => {
+                if let Some(scope) = scope {
+                    diagnostics.error(
+                        fragment.name.span(),
+                        format!("@fragment `{}` can't be used {}", fragment.name, scope),
+                    );
+                }
+                fragments(&fragment.body, scope, diagnostics);
+            }
+            Node::Stmt(syn::Stmt::Local(_)) => scope = Some("after @let"),
+            Node::Expr(_) | Node::Stmt(_) | Node::Raw(_) | Node::Pre(_) => {}
+        }
+    }
+    scope
+}
+
+fn branches<'a>(
+    branches: impl Iterator<Item = &'a [Node]>,
+    seen: &mut Vec<&'a syn::LitStr>,
+    in_loop: bool,
+    diagnostics: &mut Diagnostics,
+) {
+    let base = seen.len();
+    let mut added = Vec::new();
+    for branch in branches {
+        ids(branch, seen, in_loop, diagnostics);
+        added.extend(seen.drain(base..));
+    }
+    seen.extend(added);
+}
+
+const TEXT_INPUTS: &[&str] = &[
+    "email", "number", "password", "search", "tel", "text", "url",
+];
+
+// Accessibility checks enabled with `#[markup(a11y)]`. Elements with a spread or a dynamic
+// attribute name are skipped, since any attribute could be among them.
+pub fn a11y(nodes: &[Node], in_label: bool, diagnostics: &mut Diagnostics) {
+    for node in nodes {
+        match node {
+            Node::Element(element) => {
+                let name = lit_str(&element.name);
+                if let (Some(name), Some(attributes)) = (name, static_attributes(element)) {
+                    a11y_element(element, name, &attributes, in_label, diagnostics);
+                }
+                let label = matches!(name, Some(name) if name.value() == "label");
+                a11y(&element.children, in_label || label, diagnostics);
+            }
+            Node::If(if_) => {
+                for clause in &if_.clauses {
+                    a11y(&clause.consequent, in_label, diagnostics);
+                }
+                a11y(
+                    if_.default.as_deref().unwrap_or_default(),
+                    in_label,
+                    diagnostics,
+                );
+            }
+            Node::Match(match_) => {
+                for clause in &match_.clauses {
+                    a11y(&clause.consequent, in_label, diagnostics);
+                }
+            }
+            Node::For(for_) => a11y(&for_.body, in_label, diagnostics),
+            Node::Cache(cache) => a11y(&cache.body, in_label, diagnostics),
+            Node::Fragment(fragment) => a11y(&fragment.body, in_label, diagnostics),
+            Node::Cfg(cfg) => {
+                a11y(&cfg.body, in_label, diagnostics);
+                a11y(
+                    cfg.default.as_deref().unwrap_or_default(),
+                    in_label,
+                    diagnostics,
+                );
+            }
+            Node::Expr(_) | Node::Stmt(_) | Node::Raw(_) | Node::Pre(_) => {}
+        }
+    }
+}
+
+fn a11y_element(
+    element: &Element,
+    name: &syn::LitStr,
+    attributes: &[(String, &syn::Expr)],
+    in_label: bool,
+    diagnostics: &mut Diagnostics,
+) {
+    let has = |name: &str| attributes.iter().any(|(n, _)| n == name);
+    let span = name.span();
+    match &*name.value() {
+        "img" if !has("alt") => diagnostics.warning(span, "`img` without an `alt` attribute"),
+        "a" if element.children.is_empty() && !has("aria-label") && !has("aria-labelledby") => {
+            diagnostics.warning(
+                span,
+                "`a` without text content or an `aria-label` attribute",
+            )
+        }
+        "input" if !in_label => {
+            let text = match attributes.iter().find(|(n, _)| n == "type") {
+                None => true,
+                Some((_, value)) => {
+                    matches!(lit_str(value), Some(value) if TEXT_INPUTS.contains(&&*value.value()))
+                }
+            };
+            // An id suggests a `label[for]` elsewhere. That can't be checked here, so it is
+            // accepted as well.
+            let id = element.id.is_some() || has("id");
+            if text && !id && !has("aria-label") && !has("aria-labelledby") {
+                diagnostics.warning(
+                    span,
+                    "text `input` without a label: wrap it in `label` or add `aria-label`, `aria-labelledby` or an `id`",
+                )
+            }
+        }
+        _ => {}
+    }
+}
+
+// The attributes of an element, or `None` if some names are only known at runtime.
+fn static_attributes(element: &Element) -> Option<Vec<(String, &syn::Expr)>> {
+    element
+        .attributes
+        .iter()
+        .map(|attribute| match attribute {
+            Attribute::One(name, value) => lit_str(name).map(|name| (name.value(), value)),
+            Attribute::Many(_) => None,
+        })
+        .collect()
+}
+
+fn static_ids(element: &Element) -> Vec<&syn::LitStr> {
+    let mut ids = Vec::new();
+    ids.extend(element.id.as_ref().and_then(lit_str));
+    for attribute in &element.attributes {
+        if let Attribute::One(name, value) = attribute {
+            if lit_str(name).map(|name| name.value()).as_deref() == Some("id") {
+                ids.extend(lit_str(value));
+            }
+        }
+    }
+    ids
+}
+
+fn lit_str(expr: &syn::Expr) -> Option<&syn::LitStr> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(lit_str),
+            ..
+        }) => Some(lit_str),
+        _ => None,
+    }
+}
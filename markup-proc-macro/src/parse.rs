@@ -1,30 +1,38 @@
 use crate::ast::{
-    Attribute, Element, For, If, IfClause, IfClauseTest, Match, MatchClause, Node, Struct, Template,
+    Attribute, Cache, Cfg, Element, For, Fragment, If, IfClause, IfClauseTest, Match, MatchClause,
+    Node, Options, Render, RenderImpl, Struct, Template,
 };
+use proc_macro2::{Delimiter, TokenStream};
 use syn::parse::{Parse, ParseStream, Result};
 use syn::punctuated::Punctuated;
 
 impl Parse for Struct {
     fn parse(input: ParseStream) -> Result<Self> {
-        let start_input_len = input.to_string().len();
-        let attributes = input.call(syn::Attribute::parse_outer)?;
+        let mut attributes = input.call(syn::Attribute::parse_outer)?;
+        let options = Options::extract(&mut attributes)?;
         let name = input.parse()?;
         let generics = input.parse()?;
-        let fields = {
-            if input.peek(syn::token::Paren) {
-                let fields;
-                syn::parenthesized!(fields in input);
-                Punctuated::<syn::Field, syn::token::Comma>::parse_terminated_with(
-                    &fields,
-                    syn::Field::parse_named,
-                )?
-                .into_pairs()
-                .map(|pair| pair.into_value())
-                .collect()
-            } else {
-                Vec::new()
+        let mut fields = Vec::new();
+        let mut rest = None;
+        if input.peek(syn::token::Paren) {
+            let inner;
+            syn::parenthesized!(inner in input);
+            while !inner.is_empty() {
+                if inner.peek(syn::Token![..]) {
+                    let _: syn::Token![..] = inner.parse()?;
+                    rest = Some(inner.parse()?);
+                    let _: Option<syn::Token![,]> = inner.parse()?;
+                    if !inner.is_empty() {
+                        return Err(inner.error("`..rest` must be the last field"));
+                    }
+                    break;
+                }
+                fields.push(inner.call(syn::Field::parse_named)?);
+                if !inner.is_empty() {
+                    let _: syn::Token![,] = inner.parse()?;
+                }
             }
-        };
+        }
         let where_clause = if input.peek(syn::token::Where) {
             Some(input.parse()?)
         } else {
@@ -36,30 +44,106 @@ impl Parse for Struct {
         while !inner.is_empty() {
             children.push(inner.parse()?);
         }
-        // We use the length of the tokens that define this template as a rough estimate of the
-        // number of bytes the output of this template will occupy.
-        // Lifted from Maud [1].
-        // [1]: https://github.com/lfairy/maud/blob/13a5cfcaa31b3f6e2deb015ea49ef87d285cef7c/maud_macros/src/lib.rs#L38-L40
-        let size_hint = start_input_len - input.to_string().len();
+        if options.trim {
+            crate::trim::trim(&mut children);
+        }
         Ok(Struct {
             name,
             attributes,
+            options,
             generics,
             where_clause,
             fields,
+            rest,
             children,
-            size_hint,
         })
     }
 }
 
+impl Options {
+    // Removes `#[markup(...)]` attributes, which configure code generation and are not forwarded
+    // to the generated struct.
+    fn extract(attributes: &mut Vec<syn::Attribute>) -> Result<Self> {
+        let mut options = Options::default();
+        let mut result = Ok(());
+        attributes.retain(|attribute| {
+            if !attribute.path().is_ident("markup") {
+                return true;
+            }
+            if result.is_ok() {
+                result = attribute.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("dyn_write") {
+                        options.dyn_write = true;
+                        Ok(())
+                    } else if meta.path.is_ident("a11y") {
+                        options.a11y = true;
+                        Ok(())
+                    } else if meta.path.is_ident("trim") {
+                        options.trim = true;
+                        Ok(())
+                    } else if meta.path.is_ident("xml") {
+                        options.xml = true;
+                        Ok(())
+                    } else if meta.path.is_ident("legacy_escaping") {
+                        options.legacy_escaping = true;
+                        Ok(())
+                    } else {
+                        Err(meta.error("unknown markup option"))
+                    }
+                });
+            }
+            false
+        });
+        result.map(|()| options)
+    }
+}
+
 impl Parse for Template {
     fn parse(input: ParseStream) -> Result<Self> {
+        // `markup::new!` closures always capture by value, `move { ... }` only spells that out.
+        if input.peek(syn::Token![move]) && input.peek2(syn::token::Brace) {
+            let _: syn::Token![move] = input.parse()?;
+            let inner;
+            syn::braced!(inner in input);
+            let children = inner.parse::<Many<Node>>()?.0;
+            return Ok(Self { children });
+        }
         let children = input.parse::<Many<Node>>()?.0;
         Ok(Self { children })
     }
 }
 
+impl Parse for Render {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let writer = input.parse()?;
+        let _: syn::Token![,] = input.parse()?;
+        let inner;
+        syn::braced!(inner in input);
+        let template = inner.parse()?;
+        if input.peek(syn::Token![,]) {
+            let _: syn::Token![,] = input.parse()?;
+        }
+        Ok(Render { writer, template })
+    }
+}
+
+impl Parse for RenderImpl {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let _: syn::Token![for] = input.parse()?;
+        let mut generics: syn::Generics = input.parse()?;
+        let ty = input.parse()?;
+        generics.where_clause = input.parse()?;
+        let inner;
+        syn::braced!(inner in input);
+        let children = inner.parse::<Many<Node>>()?.0;
+        Ok(RenderImpl {
+            generics,
+            ty,
+            children,
+        })
+    }
+}
+
 impl Parse for Node {
     fn parse(input: ParseStream) -> Result<Self> {
         let lookahead = input.lookahead1();
@@ -81,6 +165,36 @@ impl Parse for Node {
             } else if lookahead.peek(syn::token::Match) {
                 let _: syn::token::Match = input.parse()?;
                 Ok(Node::Match(input.parse()?))
+            } else if peek_keyword(input, "raw", &[Delimiter::Brace])
+                || peek_keyword(input, "raw", &[Delimiter::Parenthesis])
+            {
+                let _: syn::Ident = input.parse()?;
+                Ok(Node::Raw(input.parse::<Raw>()?.0))
+            } else if peek_keyword(input, "pre", &[Delimiter::Brace]) {
+                let _: syn::Ident = input.parse()?;
+                let inner;
+                syn::braced!(inner in input);
+                let span = inner.span();
+                let mut string = String::new();
+                while !inner.is_empty() {
+                    string.push_str(&inner.parse::<syn::LitStr>()?.value());
+                }
+                Ok(Node::Pre(syn::LitStr::new(&dedent(&string), span)))
+            } else if peek_keyword(input, "cache", &[Delimiter::Parenthesis, Delimiter::Brace]) {
+                let _: syn::Ident = input.parse()?;
+                Ok(Node::Cache(input.parse()?))
+            } else if peek_keyword(input, "cfg", &[Delimiter::Parenthesis, Delimiter::Brace]) {
+                let _: syn::Ident = input.parse()?;
+                Ok(Node::Cfg(input.parse()?))
+            } else if peek_fragment(input) {
+                let _: syn::Ident = input.parse()?;
+                let name = input.parse()?;
+                let body;
+                syn::braced!(body in input);
+                let body = body.parse::<Many<_>>()?.0;
+                Ok(Node::Fragment(Fragment { name, body }))
+            } else if let Some(component) = component(input)? {
+                Ok(Node::Expr(component))
             } else if lookahead.peek(syn::Lit)
                 || lookahead.peek(syn::Ident)
                 || lookahead.peek(syn::token::Brace)
@@ -89,7 +203,13 @@ impl Parse for Node {
                 || lookahead.peek(syn::token::SelfType)
                 || lookahead.peek(syn::token::Super)
             {
-                Ok(Node::Expr(input.parse()?))
+                let expr = input.parse()?;
+                // `@expr;` is evaluated for its side effects.
+                if input.peek(syn::Token![;]) {
+                    Ok(Node::Stmt(syn::Stmt::Expr(expr, Some(input.parse()?))))
+                } else {
+                    Ok(Node::Expr(expr))
+                }
             } else if input.fork().parse::<syn::Stmt>().is_ok() {
                 Ok(Node::Stmt(input.parse()?))
             } else {
@@ -106,6 +226,71 @@ impl Parse for Node {
     }
 }
 
+impl Parse for Cache {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let arguments;
+        syn::parenthesized!(arguments in input);
+        let key = arguments.parse()?;
+        let _: syn::Token![,] = arguments.parse()?;
+        let cache = arguments.parse()?;
+        if arguments.peek(syn::Token![,]) {
+            let _: syn::Token![,] = arguments.parse()?;
+        }
+        let body;
+        syn::braced!(body in input);
+        let body = body.parse::<Many<_>>()?.0;
+        Ok(Cache { key, cache, body })
+    }
+}
+
+impl Parse for Cfg {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let predicate;
+        syn::parenthesized!(predicate in input);
+        let predicate = predicate.parse()?;
+        let body;
+        syn::braced!(body in input);
+        let body = body.parse::<Many<_>>()?.0;
+        let fork = input.fork();
+        let default = if fork.parse::<syn::Token![@]>().is_ok()
+            && peek_keyword(&fork, "cfg_else", &[Delimiter::Brace])
+        {
+            let _: syn::Token![@] = input.parse()?;
+            let _: syn::Ident = input.parse()?;
+            let default;
+            syn::braced!(default in input);
+            Some(default.parse::<Many<_>>()?.0)
+        } else {
+            None
+        };
+        Ok(Cfg {
+            predicate,
+            body,
+            default,
+        })
+    }
+}
+
+struct Raw(syn::Expr);
+
+impl Parse for Raw {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let inner;
+        if input.peek(syn::token::Paren) {
+            syn::parenthesized!(inner in input);
+            return Ok(Raw(inner.parse()?));
+        }
+        syn::braced!(inner in input);
+        let mut string = String::new();
+        let span = inner.span();
+        while !inner.is_empty() {
+            string.push_str(&inner.parse::<syn::LitStr>()?.value());
+        }
+        let string = syn::LitStr::new(&string, span);
+        Ok(Raw(syn::parse_quote!(#string)))
+    }
+}
+
 impl Parse for Element {
     fn parse(input: ParseStream) -> Result<Self> {
         let (name, mut id, mut classes) = {
@@ -277,12 +462,18 @@ impl Parse for MatchClause {
 impl Parse for For {
     fn parse(input: ParseStream) -> Result<Self> {
         let pat = syn::Pat::parse_multi_with_leading_vert(input)?;
+        let fallible = input.parse::<Option<syn::Token![?]>>()?.is_some();
         let _: syn::token::In = input.parse()?;
         let expr = syn::Expr::parse_without_eager_brace(input)?;
         let body;
         syn::braced!(body in input);
         let body = body.parse::<Many<_>>()?.0;
-        Ok(For { pat, expr, body })
+        Ok(For {
+            pat,
+            fallible,
+            expr,
+            body,
+        })
     }
 }
 
@@ -304,6 +495,129 @@ impl Parse for Attribute {
     }
 }
 
+// `@Component { field: value, name = value }` constructs a component declared with a `..rest`
+// field, which receives the `name = value` attributes. Without any attribute, it's left to be
+// parsed as a regular struct expression.
+fn component(input: ParseStream) -> Result<Option<syn::Expr>> {
+    struct Component {
+        path: syn::Path,
+        fields: Vec<TokenStream>,
+        attributes: Vec<(syn::Expr, syn::Expr)>,
+    }
+
+    fn parse(input: ParseStream) -> Result<Component> {
+        use syn::ext::IdentExt;
+        let path = input.parse()?;
+        let inner;
+        syn::braced!(inner in input);
+        let mut fields = Vec::new();
+        let mut attributes = Vec::new();
+        while !inner.is_empty() {
+            if inner.peek(syn::token::Brace)
+                || ((inner.peek(syn::Ident::peek_any) || inner.peek(syn::LitStr))
+                    && inner.peek2(syn::Token![=]))
+            {
+                let name = identifier_or_string_literal_or_expression(&inner)?;
+                let _: syn::Token![=] = inner.parse()?;
+                attributes.push((name, inner.parse()?));
+            } else {
+                let member: syn::Ident = inner.parse()?;
+                if inner.peek(syn::Token![:]) {
+                    let _: syn::Token![:] = inner.parse()?;
+                    let value: syn::Expr = inner.parse()?;
+                    fields.push(quote::quote!(#member: #value));
+                } else {
+                    fields.push(quote::quote!(#member));
+                }
+            }
+            if !inner.is_empty() {
+                let _: syn::Token![,] = inner.parse()?;
+            }
+        }
+        Ok(Component {
+            path,
+            fields,
+            attributes,
+        })
+    }
+
+    match parse(&input.fork()) {
+        Ok(component) if !component.attributes.is_empty() => {}
+        _ => return Ok(None),
+    }
+    let Component {
+        path,
+        fields,
+        attributes,
+    } = parse(input)?;
+    let (names, values): (Vec<_>, Vec<_>) = attributes.into_iter().unzip();
+    Ok(Some(syn::parse_quote! {
+        #path {
+            #(#fields,)*
+            __markup_rest: {
+                let mut __attrs = ::markup::AttrMap::new();
+                #(__attrs.insert(#names, #values)?;)*
+                __attrs
+            },
+        }
+    }))
+}
+
+// `@keyword` nodes are only recognized when followed by the expected delimiters, so that calls
+// to functions with the same name keep working as expressions.
+fn peek_keyword(input: ParseStream, keyword: &str, delimiters: &[Delimiter]) -> bool {
+    let mut cursor = match input.cursor().ident() {
+        Some((ident, cursor)) if ident == keyword => cursor,
+        _ => return false,
+    };
+    for &delimiter in delimiters {
+        match cursor.group(delimiter) {
+            Some((_, _, next)) => cursor = next,
+            None => return false,
+        }
+    }
+    true
+}
+
+// `@fragment name { ... }`
+fn peek_fragment(input: ParseStream) -> bool {
+    let cursor = match input.cursor().ident() {
+        Some((ident, cursor)) if ident == "fragment" => cursor,
+        _ => return false,
+    };
+    match cursor.ident() {
+        Some((_, cursor)) => cursor.group(Delimiter::Brace).is_some(),
+        None => false,
+    }
+}
+
+// Removes a leading line break, trailing whitespace after the last line break, and the longest
+// common indentation (spaces and tabs) of all lines which aren't blank. Blank lines are emptied.
+fn dedent(string: &str) -> String {
+    let string = string.strip_prefix('\n').unwrap_or(string);
+    let string = match string.rfind('\n') {
+        Some(index) if string[index..].trim().is_empty() => &string[..index],
+        _ => string,
+    };
+    let indent = string
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start_matches(&[' ', '\t'][..]).len())
+        .min()
+        .unwrap_or(0);
+    string
+        .split('\n')
+        .map(|line| {
+            if line.trim().is_empty() {
+                ""
+            } else {
+                &line[indent..]
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[derive(Debug)]
 pub struct Many<P>(pub Vec<P>);
 
@@ -317,15 +631,24 @@ impl<P: Parse> Parse for Many<P> {
     }
 }
 
+#[test]
+fn test_dedent() {
+    assert_eq!(dedent(""), "");
+    assert_eq!(dedent("a"), "a");
+    assert_eq!(dedent("\n    a\n      b\n    "), "a\n  b");
+    assert_eq!(dedent("\n\ta\n\n  \n\tb\n"), "a\n\n\nb");
+    assert_eq!(dedent("  a\n b"), " a\nb");
+}
+
 fn identifier_or_string_literal_or_expression(input: ParseStream) -> Result<syn::Expr> {
     use syn::ext::IdentExt;
     let lookahead = input.lookahead1();
     if lookahead.peek(syn::Ident::peek_any) {
         let ident = syn::Ident::parse_any(input)?;
-        let string = ident.unraw().to_string();
+        let string = syn::LitStr::new(&ident.unraw().to_string(), ident.span());
         Ok(syn::parse_quote!(#string))
     } else if lookahead.peek(syn::LitStr) {
-        let string = input.parse::<syn::LitStr>()?.value();
+        let string = input.parse::<syn::LitStr>()?;
         Ok(syn::parse_quote!(#string))
     } else if lookahead.peek(syn::token::Brace) {
         let inner;
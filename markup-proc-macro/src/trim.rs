@@ -0,0 +1,106 @@
+use crate::ast::{Element, Node};
+
+// Elements whose contents are kept as written.
+const PRESERVED: &[&str] = &["pre", "textarea", "script", "style"];
+
+// Removes insignificant whitespace from the string literals of a `#[markup(trim)]` template. A
+// run of whitespace containing a newline is dropped where it meets a tag or the start or end of
+// a block, every other run becomes a single space.
+pub fn trim(nodes: &mut Vec<Node>) {
+    for index in 0..nodes.len() {
+        let after_tag = index == 0 || matches!(nodes[index - 1], Node::Element(_));
+        let before_tag = matches!(nodes.get(index + 1), None | Some(Node::Element(_)));
+        match &mut nodes[index] {
+            Node::Element(element) => {
+                if !preserved(element) {
+                    trim(&mut element.children);
+                }
+            }
+            Node::If(if_) => {
+                for clause in &mut if_.clauses {
+                    trim(&mut clause.consequent);
+                }
+                if let Some(default) = &mut if_.default {
+                    trim(default);
+                }
+            }
+            Node::Match(match_) => {
+                for clause in &mut match_.clauses {
+                    trim(&mut clause.consequent);
+                }
+            }
+            Node::For(for_) => trim(&mut for_.body),
+            Node::Cache(cache) => trim(&mut cache.body),
+            Node::Fragment(fragment) => trim(&mut fragment.body),
+            Node::Cfg(cfg) => {
+                trim(&mut cfg.body);
+                if let Some(default) = &mut cfg.default {
+                    trim(default);
+                }
+            }
+            Node::Expr(syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(lit_str),
+                ..
+            })) => {
+                let trimmed = collapse(&lit_str.value(), after_tag, before_tag);
+                *lit_str = syn::LitStr::new(&trimmed, lit_str.span());
+            }
+            Node::Expr(_) | Node::Stmt(_) | Node::Raw(_) | Node::Pre(_) => {}
+        }
+    }
+    nodes.retain(|node| match node {
+        Node::Expr(syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(lit_str),
+            ..
+        })) => !lit_str.value().is_empty(),
+        _ => true,
+    });
+}
+
+fn preserved(element: &Element) -> bool {
+    match &element.name {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(lit_str),
+            ..
+        }) => PRESERVED.contains(&&*lit_str.value()),
+        _ => false,
+    }
+}
+
+fn collapse(str: &str, after_tag: bool, before_tag: bool) -> String {
+    let mut string = String::with_capacity(str.len());
+    let mut chars = str.chars().peekable();
+    while let Some(char) = chars.next() {
+        if !char.is_ascii_whitespace() {
+            string.push(char);
+            continue;
+        }
+        let mut newline = char == '\n';
+        while let Some(&char) = chars.peek().filter(|char| char.is_ascii_whitespace()) {
+            newline |= char == '\n';
+            chars.next();
+        }
+        let at_tag = if string.is_empty() {
+            after_tag
+        } else {
+            chars.peek().is_none() && before_tag
+        };
+        if !(newline && at_tag) {
+            string.push(' ');
+        }
+    }
+    string
+}
+
+#[test]
+fn test_collapse() {
+    assert_eq!(
+        collapse("\n    Hello,\n    world!\n  ", true, true),
+        "Hello, world!"
+    );
+    assert_eq!(collapse("\n  Hello  ", false, false), " Hello ");
+    assert_eq!(collapse("  a \t b  ", true, true), " a b ");
+    assert_eq!(collapse("\n  ", true, false), "");
+    assert_eq!(collapse("\n  ", false, false), " ");
+    assert_eq!(collapse("a\u{a0}\u{a0}b", true, true), "a\u{a0}\u{a0}b");
+}
@@ -0,0 +1,92 @@
+use crate::{Render, RenderAttributeValue, RenderError};
+use std::borrow::Cow;
+
+/// Attributes collected at runtime, in insertion order.
+///
+/// A template declared with a `..rest` field, e.g. `Input<'a>(label: &'a str, ..rest)`, receives
+/// the `name = value` arguments of `@Input { label: "Name", placeholder = "Jane" }` in an
+/// `AttrMap` bound to `rest`, which can be spread on an element with `input[..rest]`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AttrMap(Vec<(Cow<'static, str>, AttrValue)>);
+
+/// A rendered attribute value. Boolean attributes which are present have no value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttrValue(Option<String>);
+
+impl AttrMap {
+    pub fn new() -> Self {
+        AttrMap(Vec::new())
+    }
+
+    /// Adds an attribute, rendering its value right away. Values which wouldn't render the
+    /// attribute, like `false` and `None`, are skipped.
+    pub fn insert(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        value: impl RenderAttributeValue,
+    ) -> Result<(), RenderError> {
+        if value.is_none() || value.is_false() {
+            return Ok(());
+        }
+        let value = if value.is_true() {
+            AttrValue(None)
+        } else {
+            let mut string = String::new();
+            value.render(&mut string)?;
+            AttrValue(Some(string))
+        };
+        self.0.push((name.into(), value));
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AttrValue> {
+        self.iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &AttrValue)> {
+        self.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a AttrMap {
+    type Item = (&'a str, &'a AttrValue);
+    type IntoIter = std::iter::Map<
+        std::slice::Iter<'a, (Cow<'static, str>, AttrValue)>,
+        fn(&'a (Cow<'static, str>, AttrValue)) -> (&'a str, &'a AttrValue),
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter().map(|(name, value)| (&**name, value))
+    }
+}
+
+impl AttrValue {
+    /// The escaped value, or `None` for a boolean attribute.
+    pub fn as_str(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
+}
+
+// The value was escaped when it was inserted.
+impl Render for AttrValue {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        writer.write_str(self.0.as_deref().unwrap_or(""))?;
+        Ok(())
+    }
+}
+
+impl RenderAttributeValue for AttrValue {
+    fn is_true(&self) -> bool {
+        self.0.is_none()
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{Render, RenderAttributeValue, RenderError};
+use std::io;
+
+/// A byte buffer to render templates into, which keeps its allocation between renders.
+///
+/// [`Buffer::render`] clears the buffer before rendering, so a buffer kept in a thread local or
+/// an object pool grows to the size of the largest page once and is reused for every request
+/// after that. Bytes can also be added with [`io::Write`]. Rendering a `Buffer` writes its
+/// contents as is, without escaping, so pre-rendered output can be embedded in another template;
+/// it fails if the contents aren't valid UTF-8.
+#[derive(Clone, Debug, Default)]
+pub struct Buffer {
+    bytes: Vec<u8>,
+}
+
+struct Utf8<'a>(&'a mut Vec<u8>);
+
+impl std::fmt::Write for Utf8<'_> {
+    #[inline]
+    fn write_str(&mut self, str: &str) -> std::fmt::Result {
+        self.0.extend_from_slice(str.as_bytes());
+        Ok(())
+    }
+}
+
+impl Buffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Buffer {
+            bytes: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Clears the buffer and renders `template` into it. If rendering fails, the buffer holds the
+    /// output written before the error.
+    pub fn render(&mut self, template: impl Render) -> Result<&[u8], RenderError> {
+        self.bytes.clear();
+        template.render(&mut Utf8(&mut self.bytes))?;
+        Ok(&self.bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.bytes.capacity()
+    }
+
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+    }
+
+    /// Takes the contents out as a `String`, leaving the buffer empty. The allocation goes with the
+    /// string, so the next render allocates again. If the contents aren't valid UTF-8, the buffer
+    /// is left as it was.
+    pub fn take_string(&mut self) -> Result<String, std::str::Utf8Error> {
+        std::str::from_utf8(&self.bytes)?;
+        let bytes = std::mem::take(&mut self.bytes);
+        Ok(String::from_utf8(bytes).expect("checked above"))
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl From<Vec<u8>> for Buffer {
+    fn from(bytes: Vec<u8>) -> Self {
+        Buffer { bytes }
+    }
+}
+
+impl io::Write for Buffer {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bytes.write(buf)
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.bytes.extend_from_slice(buf);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Render for Buffer {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        let str = std::str::from_utf8(&self.bytes).map_err(RenderError::wrap)?;
+        Ok(writer.write_str(str)?)
+    }
+}
+
+impl RenderAttributeValue for Buffer {}
+
+const CHUNK_SIZE: usize = 8 * 1024;
+
+// Collects small writes into chunks of up to 8 KiB. Larger writes go straight to the writer.
+struct Buffered<'a, W> {
+    inner: &'a mut W,
+    chunk: Vec<u8>,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> Buffered<'_, W> {
+    fn flush_chunk(&mut self) -> io::Result<()> {
+        let result = self.inner.write_all(&self.chunk);
+        self.chunk.clear();
+        result
+    }
+}
+
+impl<W: io::Write> std::fmt::Write for Buffered<'_, W> {
+    fn write_str(&mut self, str: &str) -> std::fmt::Result {
+        let mut write = || {
+            if self.chunk.len() + str.len() > CHUNK_SIZE {
+                self.flush_chunk()?;
+            }
+            if str.len() >= CHUNK_SIZE {
+                self.inner.write_all(str.as_bytes())
+            } else {
+                self.chunk.extend_from_slice(str.as_bytes());
+                Ok(())
+            }
+        };
+        write().map_err(|error| {
+            self.error = Some(error);
+            std::fmt::Error
+        })
+    }
+}
+
+pub(crate) fn render_buffered(
+    template: &(impl Render + ?Sized),
+    writer: &mut impl io::Write,
+) -> Result<(), RenderError> {
+    let mut buffered = Buffered {
+        inner: writer,
+        chunk: Vec::with_capacity(CHUNK_SIZE),
+        error: None,
+    };
+    let result = template.render(&mut buffered);
+    if let Some(error) = buffered.error {
+        return Err(RenderError::wrap(error));
+    }
+    result?;
+    buffered
+        .flush_chunk()
+        .and_then(|()| buffered.inner.flush())
+        .map_err(RenderError::wrap)
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A store for fragments rendered by `@cache(key, &cache) { ... }`.
+pub trait Cache<K> {
+    fn get(&self, key: &K) -> Option<Arc<str>>;
+
+    fn insert(&self, key: K, fragment: String);
+}
+
+impl<K, C: Cache<K> + ?Sized> Cache<K> for &C {
+    #[inline]
+    fn get(&self, key: &K) -> Option<Arc<str>> {
+        C::get(self, key)
+    }
+
+    #[inline]
+    fn insert(&self, key: K, fragment: String) {
+        C::insert(self, key, fragment)
+    }
+}
+
+/// An in-memory [`Cache`], optionally expiring fragments after a fixed duration.
+pub struct MemoCache<K> {
+    ttl: Option<Duration>,
+    fragments: Mutex<HashMap<K, (Instant, Arc<str>)>>,
+}
+
+impl<K: Hash + Eq> MemoCache<K> {
+    pub fn new() -> Self {
+        MemoCache {
+            ttl: None,
+            fragments: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_ttl(ttl: Duration) -> Self {
+        MemoCache {
+            ttl: Some(ttl),
+            ..MemoCache::new()
+        }
+    }
+
+    pub fn clear(&self) {
+        self.fragments.lock().unwrap().clear();
+    }
+}
+
+impl<K: Hash + Eq> Default for MemoCache<K> {
+    fn default() -> Self {
+        MemoCache::new()
+    }
+}
+
+impl<K: Hash + Eq> Cache<K> for MemoCache<K> {
+    fn get(&self, key: &K) -> Option<Arc<str>> {
+        let mut fragments = self.fragments.lock().unwrap();
+        let (inserted, fragment) = fragments.get(key)?;
+        match self.ttl {
+            Some(ttl) if inserted.elapsed() >= ttl => {
+                fragments.remove(key);
+                None
+            }
+            _ => Some(fragment.clone()),
+        }
+    }
+
+    fn insert(&self, key: K, fragment: String) {
+        let fragment = (Instant::now(), fragment.into());
+        self.fragments.lock().unwrap().insert(key, fragment);
+    }
+}
@@ -0,0 +1,77 @@
+/// A writer which discards its output and counts the bytes written to it, e.g. to know the
+/// `Content-Length` of a response before sending it. It implements both [`std::fmt::Write`] and
+/// [`std::io::Write`]. The count is a `u64`, so it doesn't overflow on 32-bit targets.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CountingWriter {
+    count: u64,
+}
+
+impl CountingWriter {
+    pub fn new() -> Self {
+        CountingWriter::default()
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl std::fmt::Write for CountingWriter {
+    #[inline]
+    fn write_str(&mut self, str: &str) -> std::fmt::Result {
+        self.count += str.len() as u64;
+        Ok(())
+    }
+}
+
+impl std::io::Write for CountingWriter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.count += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A writer which writes everything to both `first` and `second`, see [`tee`].
+pub struct Tee<A, B> {
+    first: A,
+    second: B,
+}
+
+/// Writes to both `first` and `second`, e.g. to count or hash the output while sending it. Every
+/// write goes to `first` and then to `second`, and fails as soon as one of them fails, which
+/// aborts the render.
+pub fn tee<A, B>(first: A, second: B) -> Tee<A, B> {
+    Tee { first, second }
+}
+
+impl<A, B> Tee<A, B> {
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A: std::fmt::Write, B: std::fmt::Write> std::fmt::Write for Tee<A, B> {
+    fn write_str(&mut self, str: &str) -> std::fmt::Result {
+        self.first.write_str(str)?;
+        self.second.write_str(str)
+    }
+}
+
+impl<A: std::io::Write, B: std::io::Write> std::io::Write for Tee<A, B> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.first.write_all(buf)?;
+        self.second.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.first.flush()?;
+        self.second.flush()
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{Render, RenderError};
+use std::time::{Duration, Instant};
+
+/// The error returned when a render takes longer than the budget of a [`DeadlineWriter`].
+#[derive(Debug, PartialEq)]
+pub struct RenderDeadlineExceeded {
+    pub budget: Duration,
+    pub elapsed: Duration,
+}
+
+impl std::fmt::Display for RenderDeadlineExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "render exceeded its budget of {:?} ({:?} elapsed)",
+            self.budget, self.elapsed
+        )
+    }
+}
+
+impl std::error::Error for RenderDeadlineExceeded {}
+
+/// A writer which fails once more than `budget` has passed since it was created.
+///
+/// The clock is read every 64 writes by default, see [`DeadlineWriter::check_every`]. After the
+/// deadline, every write fails with [`std::fmt::Error`] and [`DeadlineWriter::exceeded`] returns
+/// the details; [`render_with_deadline`] turns that into a [`RenderDeadlineExceeded`] error.
+/// Whatever was written before the deadline is still in the inner writer, so the caller should
+/// discard it, or abort the response if it was already being sent.
+pub struct DeadlineWriter<W> {
+    inner: W,
+    start: Instant,
+    budget: Duration,
+    every: u32,
+    writes: u32,
+    exceeded: Option<RenderDeadlineExceeded>,
+}
+
+impl<W: std::fmt::Write> DeadlineWriter<W> {
+    pub fn new(inner: W, budget: Duration) -> Self {
+        DeadlineWriter {
+            inner,
+            start: Instant::now(),
+            budget,
+            every: 64,
+            writes: 0,
+            exceeded: None,
+        }
+    }
+
+    /// Reads the clock every `writes` writes instead. Must be at least 1.
+    pub fn check_every(mut self, writes: u32) -> Self {
+        assert!(writes > 0, "`check_every` must be at least 1");
+        self.every = writes;
+        self
+    }
+
+    pub fn exceeded(&self) -> Option<&RenderDeadlineExceeded> {
+        self.exceeded.as_ref()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: std::fmt::Write> std::fmt::Write for DeadlineWriter<W> {
+    fn write_str(&mut self, str: &str) -> std::fmt::Result {
+        if self.exceeded.is_some() {
+            return Err(std::fmt::Error);
+        }
+        self.writes += 1;
+        if self.writes >= self.every {
+            self.writes = 0;
+            let elapsed = self.start.elapsed();
+            if elapsed > self.budget {
+                self.exceeded = Some(RenderDeadlineExceeded {
+                    budget: self.budget,
+                    elapsed,
+                });
+                return Err(std::fmt::Error);
+            }
+        }
+        self.inner.write_str(str)
+    }
+}
+
+/// Renders `template` to `writer` through a [`DeadlineWriter`], failing with
+/// [`RenderDeadlineExceeded`] if it takes longer than `budget`.
+pub fn render_with_deadline(
+    template: impl Render,
+    writer: &mut impl std::fmt::Write,
+    budget: Duration,
+) -> Result<(), RenderError> {
+    let mut writer = DeadlineWriter::new(writer, budget);
+    let result = template.render(&mut writer);
+    match writer.exceeded.take() {
+        Some(exceeded) => Err(RenderError::wrap(exceeded)),
+        None => result,
+    }
+}
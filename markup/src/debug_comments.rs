@@ -0,0 +1,38 @@
+use crate::RenderError;
+use std::cell::Cell;
+
+thread_local! {
+    static SUPPRESSED: Cell<usize> = Cell::new(0);
+}
+
+pub fn start(writer: &mut impl std::fmt::Write, name: &str) -> Result<(), RenderError> {
+    comment(writer, "start", name)
+}
+
+pub fn end(writer: &mut impl std::fmt::Write, name: &str) -> Result<(), RenderError> {
+    comment(writer, "end", name)
+}
+
+fn comment(writer: &mut impl std::fmt::Write, kind: &str, name: &str) -> Result<(), RenderError> {
+    if SUPPRESSED.with(Cell::get) == 0 {
+        write!(writer, "<!-- markup:{} {} -->", kind, name)?;
+    }
+    Ok(())
+}
+
+/// Suppresses debug comments while alive. Used inside start tags and raw text elements.
+pub struct Guard(());
+
+impl Guard {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        SUPPRESSED.with(|suppressed| suppressed.set(suppressed.get() + 1));
+        Guard(())
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        SUPPRESSED.with(|suppressed| suppressed.set(suppressed.get() - 1));
+    }
+}
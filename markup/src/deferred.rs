@@ -0,0 +1,81 @@
+use crate::{Render, RenderDyn, RenderError};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+#[derive(Default)]
+struct State {
+    fragments: Vec<Box<dyn RenderDyn>>,
+    keys: HashSet<Cow<'static, str>>,
+}
+
+/// Fragments registered while rendering a page, to be written at some other point of it, e.g. a
+/// `<script>` needed by a widget, placed before `</body>`.
+///
+/// `Deferred` is a handle which can be cloned and passed to any template. Rendering
+/// [`Deferred::flush`] writes the fragments pushed so far, in the order they were pushed, and
+/// removes them. Fragments pushed after that are kept for the next flush, so a layout can flush
+/// more than once; fragments never flushed are never written. If a fragment fails to render, the
+/// flush stops with its error and the failing fragment is dropped, while the fragments after it
+/// are kept for the next flush.
+#[derive(Clone, Default)]
+pub struct Deferred(Rc<RefCell<State>>);
+
+impl Deferred {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, fragment: impl RenderDyn + 'static) {
+        self.0.borrow_mut().fragments.push(Box::new(fragment));
+    }
+
+    /// Pushes `fragment` unless a fragment with the same key was pushed before, including one
+    /// which was already flushed.
+    pub fn push_once(&self, key: impl Into<Cow<'static, str>>, fragment: impl RenderDyn + 'static) {
+        let mut state = self.0.borrow_mut();
+        if state.keys.insert(key.into()) {
+            state.fragments.push(Box::new(fragment));
+        }
+    }
+
+    /// The number of fragments waiting to be flushed.
+    pub fn len(&self) -> usize {
+        self.0.borrow().fragments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Renders the fragments pushed so far, including those pushed by the fragments themselves.
+    pub fn flush(&self) -> Flush<'_> {
+        Flush(self)
+    }
+}
+
+pub struct Flush<'a>(&'a Deferred);
+
+impl Render for Flush<'_> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        loop {
+            let fragments = std::mem::take(&mut self.0 .0.borrow_mut().fragments);
+            if fragments.is_empty() {
+                return Ok(());
+            }
+            let mut fragments = fragments.into_iter();
+            while let Some(fragment) = fragments.next() {
+                if let Err(error) = fragment.render_dyn(writer) {
+                    // The fragments after the failing one go back in front of those pushed while
+                    // rendering this batch.
+                    let mut state = self.0 .0.borrow_mut();
+                    let pushed = std::mem::take(&mut state.fragments);
+                    state.fragments.extend(fragments);
+                    state.fragments.extend(pushed);
+                    return Err(error);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,432 @@
+//! Runtime overrides of `define!` templates, enabled by the `dev-reload` feature in debug builds.
+//!
+//! If the `MARKUP_OVERRIDE_DIR` environment variable is set and the directory contains a file
+//! named `<Template>.markup`, that file is rendered instead of the compiled template. It is read
+//! again on every render. The file uses a subset of the template syntax: elements with `#id`,
+//! `.class` and `[name = value]` attributes, string literals and `{field}` (or `@field`) to
+//! render a field of the template. There is no control flow and no other expressions. If the file
+//! can't be parsed or refers to an unknown field, a warning is printed to stderr and the compiled
+//! template is rendered.
+
+use crate::{Render, RenderDyn, RenderError};
+use std::path::PathBuf;
+
+pub const OVERRIDE_DIR: &str = "MARKUP_OVERRIDE_DIR";
+
+#[doc(hidden)]
+pub struct Field<'a, T>(pub &'a T);
+
+#[doc(hidden)]
+pub trait ViaRender<'a> {
+    fn lookup(&self) -> Option<&'a dyn RenderDyn>;
+}
+
+impl<'a, T: Render> ViaRender<'a> for Field<'a, T> {
+    fn lookup(&self) -> Option<&'a dyn RenderDyn> {
+        Some(self.0)
+    }
+}
+
+// Picked by autoref when the field doesn't implement `Render`.
+#[doc(hidden)]
+pub trait ViaFallback<'a> {
+    fn lookup(&self) -> Option<&'a dyn RenderDyn>;
+}
+
+impl<'a, T> ViaFallback<'a> for &Field<'a, T> {
+    fn lookup(&self) -> Option<&'a dyn RenderDyn> {
+        None
+    }
+}
+
+pub type Fields<'a> = [(&'static str, Option<&'a dyn RenderDyn>)];
+
+/// A parsed override file.
+pub struct Override {
+    path: PathBuf,
+    nodes: Vec<Node>,
+}
+
+enum Node {
+    Text(String),
+    Field(String),
+    Element {
+        name: String,
+        id: Option<Value>,
+        classes: Vec<Value>,
+        attributes: Vec<(String, Option<Value>)>,
+        children: Option<Vec<Node>>,
+    },
+}
+
+enum Value {
+    Text(String),
+    Field(String),
+}
+
+/// Loads the override of template `name`, defined in `file`, if there is one.
+pub fn load(name: &str, file: &str) -> Option<Override> {
+    let dir = std::env::var_os(OVERRIDE_DIR)?;
+    let path = PathBuf::from(dir).join(format!("{}.markup", name));
+    let source = match std::fs::read_to_string(&path) {
+        Ok(source) => source,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(error) => {
+            warn(&path, name, file, &error);
+            return None;
+        }
+    };
+    match Parser::new(&source).parse() {
+        Ok(nodes) => Some(Override { path, nodes }),
+        Err(error) => {
+            warn(&path, name, file, &error);
+            None
+        }
+    }
+}
+
+fn warn(path: &std::path::Path, name: &str, file: &str, error: &dyn std::fmt::Display) {
+    eprintln!(
+        "markup: ignoring override {} of {} (defined in {}): {}",
+        path.display(),
+        name,
+        file,
+        error
+    );
+}
+
+impl Override {
+    /// Renders the override with `fields`. Returns `None`, after printing a warning, if it refers
+    /// to a field which isn't in `fields` or doesn't implement `Render`.
+    pub fn render(
+        &self,
+        fields: &Fields,
+        writer: &mut impl std::fmt::Write,
+    ) -> Option<Result<(), RenderError>> {
+        let mut missing = None;
+        visit(&self.nodes, &mut |field| {
+            if missing.is_none()
+                && !fields
+                    .iter()
+                    .any(|(name, value)| *name == field && value.is_some())
+            {
+                missing = Some(field.to_string());
+            }
+        });
+        if let Some(field) = missing {
+            eprintln!(
+                "markup: ignoring override {}: unknown or unrenderable field `{}`",
+                self.path.display(),
+                field
+            );
+            return None;
+        }
+        Some(render(&self.nodes, fields, writer))
+    }
+}
+
+fn visit<'a>(nodes: &'a [Node], f: &mut impl FnMut(&'a str)) {
+    fn value<'a>(value: &'a Value, f: &mut impl FnMut(&'a str)) {
+        if let Value::Field(field) = value {
+            f(field);
+        }
+    }
+    for node in nodes {
+        match node {
+            Node::Text(_) => {}
+            Node::Field(field) => f(field),
+            Node::Element {
+                id,
+                classes,
+                attributes,
+                children,
+                ..
+            } => {
+                id.iter().chain(classes).for_each(|v| value(v, f));
+                attributes
+                    .iter()
+                    .filter_map(|(_, v)| v.as_ref())
+                    .for_each(|v| value(v, f));
+                visit(children.as_deref().unwrap_or_default(), f);
+            }
+        }
+    }
+}
+
+fn field<'a>(fields: &Fields<'a>, name: &str) -> &'a dyn RenderDyn {
+    fields
+        .iter()
+        .find(|(field, _)| *field == name)
+        .and_then(|(_, value)| *value)
+        .expect("fields are checked before rendering")
+}
+
+fn render_value(
+    value: &Value,
+    fields: &Fields,
+    writer: &mut impl std::fmt::Write,
+) -> Result<(), RenderError> {
+    match value {
+        Value::Text(text) => text.render(writer),
+        Value::Field(name) => field(fields, name).render_dyn(writer),
+    }
+}
+
+fn render(
+    nodes: &[Node],
+    fields: &Fields,
+    writer: &mut impl std::fmt::Write,
+) -> Result<(), RenderError> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => text.render(writer)?,
+            Node::Field(name) => field(fields, name).render_dyn(writer)?,
+            Node::Element {
+                name,
+                id,
+                classes,
+                attributes,
+                children,
+            } => {
+                write!(writer, "<{}", name)?;
+                if let Some(id) = id {
+                    writer.write_str(" id=\"")?;
+                    render_value(id, fields, writer)?;
+                    writer.write_str("\"")?;
+                }
+                if !classes.is_empty() {
+                    writer.write_str(" class=\"")?;
+                    for (index, class) in classes.iter().enumerate() {
+                        if index > 0 {
+                            writer.write_str(" ")?;
+                        }
+                        render_value(class, fields, writer)?;
+                    }
+                    writer.write_str("\"")?;
+                }
+                for (name, value) in attributes {
+                    write!(writer, " {}", name)?;
+                    if let Some(value) = value {
+                        writer.write_str("=\"")?;
+                        render_value(value, fields, writer)?;
+                        writer.write_str("\"")?;
+                    }
+                }
+                writer.write_str(">")?;
+                if let Some(children) = children {
+                    render(children, fields, writer)?;
+                    write!(writer, "</{}>", name)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+#[derive(Debug)]
+struct ParseError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Parser {
+            source,
+            position: 0,
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.position..]
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ParseError> {
+        let before = &self.source[..self.position];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+        Err(ParseError {
+            line,
+            column,
+            message: message.into(),
+        })
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.position += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.position += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn eat(&mut self, token: char) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.position += token.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: char) -> Result<(), ParseError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            self.error(format!("expected `{}`", token))
+        }
+    }
+
+    fn name(&mut self) -> Result<String, ParseError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|char: char| !(char.is_alphanumeric() || char == '_' || char == '-'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return self.error("expected a name");
+        }
+        self.position += len;
+        Ok(rest[..len].to_string())
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.expect('"')?;
+        let mut string = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((index, char)) = chars.next() {
+            match char {
+                '"' => {
+                    self.position += index + 1;
+                    return Ok(string);
+                }
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => string.push('\n'),
+                    Some((_, 't')) => string.push('\t'),
+                    Some((_, char @ ('"' | '\\'))) => string.push(char),
+                    _ => {
+                        self.position += index;
+                        return self.error("unsupported escape sequence");
+                    }
+                },
+                _ => string.push(char),
+            }
+        }
+        self.position = self.source.len();
+        self.error("unterminated string")
+    }
+
+    fn field(&mut self) -> Result<String, ParseError> {
+        let name = self.name()?;
+        self.expect('}')?;
+        Ok(name)
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        self.skip_whitespace();
+        if self.rest().starts_with('"') {
+            Ok(Value::Text(self.string()?))
+        } else if self.eat('{') {
+            Ok(Value::Field(self.field()?))
+        } else {
+            Ok(Value::Text(self.name()?))
+        }
+    }
+
+    fn parse(mut self) -> Result<Vec<Node>, ParseError> {
+        let nodes = self.nodes()?;
+        self.skip_whitespace();
+        if !self.rest().is_empty() {
+            return self.error("unexpected input");
+        }
+        Ok(nodes)
+    }
+
+    fn nodes(&mut self) -> Result<Vec<Node>, ParseError> {
+        let mut nodes = Vec::new();
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.is_empty() || rest.starts_with('}') {
+                return Ok(nodes);
+            } else if rest.starts_with('"') {
+                nodes.push(Node::Text(self.string()?));
+            } else if self.eat('{') {
+                nodes.push(Node::Field(self.field()?));
+            } else if self.eat('@') {
+                nodes.push(Node::Field(self.name()?));
+            } else {
+                nodes.push(self.element()?);
+            }
+        }
+    }
+
+    fn element(&mut self) -> Result<Node, ParseError> {
+        let name = self.name()?;
+        let mut id = None;
+        let mut classes = Vec::new();
+        let mut attributes = Vec::new();
+        loop {
+            if self.eat('#') {
+                id = Some(self.value()?);
+            } else if self.eat('.') {
+                classes.push(self.value()?);
+            } else {
+                break;
+            }
+        }
+        if self.eat('[') {
+            while !self.eat(']') {
+                self.skip_whitespace();
+                let name = if self.rest().starts_with('"') {
+                    self.string()?
+                } else {
+                    self.name()?
+                };
+                let value = if self.eat('=') {
+                    Some(self.value()?)
+                } else {
+                    None
+                };
+                attributes.push((name, value));
+                if !self.eat(',') {
+                    self.expect(']')?;
+                    break;
+                }
+            }
+        }
+        let children = if self.eat(';') {
+            None
+        } else {
+            self.expect('{')?;
+            let children = self.nodes()?;
+            self.expect('}')?;
+            Some(children)
+        };
+        Ok(Node::Element {
+            name,
+            id,
+            classes,
+            attributes,
+            children,
+        })
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{Render, RenderError};
+use encoding_rs::{CoderResult, Encoder, Encoding};
+use std::io;
+
+/// A writer transcoding the output of a template from UTF-8 to another encoding, e.g.
+/// `encoding_rs::WINDOWS_1252`, and writing the bytes to an [`io::Write`].
+///
+/// Characters the encoding can't represent are written as decimal character references like
+/// `&#8212;`, which browsers decode in text and attribute values alike. As in the Encoding
+/// Standard, UTF-16 and the replacement encoding produce UTF-8. Call [`EncodingWriter::finish`]
+/// after the last write: stateful encodings like ISO-2022-JP end with a reset sequence. If the
+/// inner writer fails, the write fails with [`std::fmt::Error`] and the error is returned by
+/// [`EncodingWriter::error`].
+pub struct EncodingWriter<W> {
+    inner: W,
+    encoder: Encoder,
+    buffer: Vec<u8>,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> EncodingWriter<W> {
+    pub fn new(inner: W, encoding: &'static Encoding) -> Self {
+        EncodingWriter {
+            inner,
+            encoder: encoding.new_encoder(),
+            buffer: vec![0; 1024],
+            error: None,
+        }
+    }
+
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    /// Ends the output and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        self.encode("", true)?;
+        Ok(self.inner)
+    }
+
+    // The encoder keeps its state between calls, and `write_str` only ever receives whole
+    // characters, so the input can be split anywhere.
+    fn encode(&mut self, mut str: &str, last: bool) -> io::Result<()> {
+        loop {
+            let (result, read, written, _) =
+                self.encoder.encode_from_utf8(str, &mut self.buffer, last);
+            self.inner.write_all(&self.buffer[..written])?;
+            str = &str[read..];
+            if let CoderResult::InputEmpty = result {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<W: io::Write> std::fmt::Write for EncodingWriter<W> {
+    fn write_str(&mut self, str: &str) -> std::fmt::Result {
+        if self.error.is_some() {
+            return Err(std::fmt::Error);
+        }
+        self.encode(str, false).map_err(|error| {
+            self.error = Some(error);
+            std::fmt::Error
+        })
+    }
+}
+
+/// Renders `template` to `writer` in `encoding` with an [`EncodingWriter`]. Errors of the writer
+/// are returned as a [`RenderError`] whose source is the [`io::Error`].
+pub fn render_encoded(
+    template: impl Render,
+    writer: &mut impl io::Write,
+    encoding: &'static Encoding,
+) -> Result<(), RenderError> {
+    let mut writer = EncodingWriter::new(writer, encoding);
+    let result = template.render(&mut writer);
+    if let Some(error) = writer.error.take() {
+        return Err(RenderError::wrap(error));
+    }
+    result?;
+    writer.finish().map_err(RenderError::wrap)?;
+    Ok(())
+}
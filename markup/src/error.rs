@@ -0,0 +1,86 @@
+#[derive(Debug)]
+pub enum RenderError {
+    Fmt(std::fmt::Error),
+    Other(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl RenderError {
+    /// Wraps an arbitrary error. `RenderError`s and `std::fmt::Error`s are kept as they are.
+    pub fn wrap(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        Self::from(Box::new(error) as Box<dyn std::error::Error + Send + Sync>)
+    }
+
+    /// The wrapped error, if it is an `E`.
+    pub fn downcast_ref<E: std::error::Error + 'static>(&self) -> Option<&E> {
+        match self {
+            RenderError::Fmt(error) => (error as &dyn std::error::Error).downcast_ref(),
+            RenderError::Other(error) => error.downcast_ref(),
+        }
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for RenderError {
+    /// Like [`RenderError::wrap`], `RenderError`s and `std::fmt::Error`s are unboxed.
+    fn from(error: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        let error = match error.downcast::<RenderError>() {
+            Ok(error) => return *error,
+            Err(error) => error,
+        };
+        match error.downcast::<std::fmt::Error>() {
+            Ok(error) => RenderError::Fmt(*error),
+            Err(error) => RenderError::Other(error),
+        }
+    }
+}
+
+impl From<std::fmt::Error> for RenderError {
+    #[inline]
+    fn from(error: std::fmt::Error) -> Self {
+        RenderError::Fmt(error)
+    }
+}
+
+// Displays the whole context chain. The sources are those of the `anyhow::Error`, ending with the
+// root cause.
+#[cfg(feature = "anyhow")]
+#[derive(Debug)]
+struct Anyhow(anyhow::Error);
+
+#[cfg(feature = "anyhow")]
+impl std::fmt::Display for Anyhow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#}", self.0)
+    }
+}
+
+#[cfg(feature = "anyhow")]
+impl std::error::Error for Anyhow {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+#[cfg(feature = "anyhow")]
+impl From<anyhow::Error> for RenderError {
+    fn from(error: anyhow::Error) -> Self {
+        RenderError::Other(Box::new(Anyhow(error)))
+    }
+}
+
+impl std::fmt::Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenderError::Fmt(error) => error.fmt(f),
+            RenderError::Other(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RenderError::Fmt(error) => Some(error),
+            RenderError::Other(error) => Some(&**error),
+        }
+    }
+}
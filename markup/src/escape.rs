@@ -1,4 +1,91 @@
+use std::borrow::Cow;
+
+/// Escapes `&`, `<`, `>` and `"`. With the `memchr` feature, strings of 16 bytes or more are
+/// searched with `memchr`, which is many times faster on text with few of these characters and up
+/// to about twice as slow on text made mostly of them. The output is the same either way.
+#[inline]
 pub fn escape(str: &str, writer: &mut impl std::fmt::Write) -> std::fmt::Result {
+    escape_bytes::<_, false>(str, writer)
+}
+
+/// Like [`escape`], also escaping `'` as `&#39;`, so the output is safe inside single quoted
+/// attribute values too.
+#[inline]
+pub fn escape_attribute(str: &str, writer: &mut impl std::fmt::Write) -> std::fmt::Result {
+    escape_bytes::<_, true>(str, writer)
+}
+
+#[cfg(not(feature = "memchr"))]
+#[inline]
+fn escape_bytes<W: std::fmt::Write, const APOSTROPHE: bool>(
+    str: &str,
+    writer: &mut W,
+) -> std::fmt::Result {
+    escape_scalar::<_, APOSTROPHE>(str, writer)
+}
+
+// Searching with `memchr` only pays off once there is some text to skip over.
+#[cfg(feature = "memchr")]
+#[inline]
+fn escape_bytes<W: std::fmt::Write, const APOSTROPHE: bool>(
+    str: &str,
+    writer: &mut W,
+) -> std::fmt::Result {
+    if str.len() < 16 {
+        escape_scalar::<_, APOSTROPHE>(str, writer)
+    } else {
+        escape_memchr::<_, APOSTROPHE>(str, writer)
+    }
+}
+
+#[cfg(feature = "memchr")]
+fn escape_memchr<W: std::fmt::Write, const APOSTROPHE: bool>(
+    str: &str,
+    writer: &mut W,
+) -> std::fmt::Result {
+    let bytes = str.as_bytes();
+    let brackets =
+        |from: usize| memchr::memchr3(b'&', b'<', b'>', &bytes[from..]).map(|i| i + from);
+    let quotes = |from: usize| {
+        if APOSTROPHE {
+            memchr::memchr2(b'"', b'\'', &bytes[from..])
+        } else {
+            memchr::memchr(b'"', &bytes[from..])
+        }
+        .map(|i| i + from)
+    };
+    // The next occurrence of either kind, searched again only once it has been escaped.
+    let mut next_bracket = brackets(0);
+    let mut next_quote = quotes(0);
+    let mut last = 0;
+    loop {
+        let index = match (next_bracket, next_quote) {
+            (Some(bracket), Some(quote)) => bracket.min(quote),
+            (Some(index), None) | (None, Some(index)) => index,
+            (None, None) => break,
+        };
+        writer.write_str(&str[last..index])?;
+        writer.write_str(match bytes[index] {
+            b'&' => "&amp;",
+            b'<' => "&lt;",
+            b'>' => "&gt;",
+            b'"' => "&quot;",
+            _ => "&#39;",
+        })?;
+        last = index + 1;
+        if next_bracket == Some(index) {
+            next_bracket = brackets(last);
+        } else {
+            next_quote = quotes(last);
+        }
+    }
+    writer.write_str(&str[last..])
+}
+
+fn escape_scalar<W: std::fmt::Write, const APOSTROPHE: bool>(
+    str: &str,
+    writer: &mut W,
+) -> std::fmt::Result {
     let mut last = 0;
     for (index, byte) in str.bytes().enumerate() {
         macro_rules! go {
@@ -14,12 +101,17 @@ pub fn escape(str: &str, writer: &mut impl std::fmt::Write) -> std::fmt::Result
             b'<' => go!("&lt;"),
             b'>' => go!("&gt;"),
             b'"' => go!("&quot;"),
+            b'\'' if APOSTROPHE => go!("&#39;"),
             _ => {}
         }
     }
     writer.write_str(&str[last..])
 }
 
+/// A writer adapter escaping everything written to it like [`escape`]. `write_str` only returns
+/// once all of its escaped input was written to the inner writer, or with the first error of the
+/// inner writer, after which the output is incomplete and should be discarded. Every call gets
+/// whole characters, so a character can't be split between calls.
 pub struct Escape<'a, W>(pub &'a mut W);
 
 impl<W: std::fmt::Write> std::fmt::Write for Escape<'_, W> {
@@ -29,6 +121,110 @@ impl<W: std::fmt::Write> std::fmt::Write for Escape<'_, W> {
     }
 }
 
+pub struct EscapeAttribute<'a, W>(pub &'a mut W);
+
+impl<W: std::fmt::Write> std::fmt::Write for EscapeAttribute<'_, W> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        escape_attribute(s, &mut self.0)
+    }
+}
+
+// Templates render attribute values through this, on top of the escaping done by the `Render`
+// impls. `&#39;` means the same as `'` in an attribute value, so raw values are unaffected.
+#[doc(hidden)]
+pub struct Apostrophes<'a, W>(pub &'a mut W);
+
+impl<W: std::fmt::Write> std::fmt::Write for Apostrophes<'_, W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        let mut parts = s.split('\'');
+        if let Some(first) = parts.next() {
+            self.0.write_str(first)?;
+        }
+        for part in parts {
+            self.0.write_str("&#39;")?;
+            self.0.write_str(part)?;
+        }
+        Ok(())
+    }
+}
+
+/// Escapes `str`, borrowing it if there is nothing to escape.
+pub fn escape_str(str: &str) -> Cow<'_, str> {
+    if str
+        .bytes()
+        .any(|byte| matches!(byte, b'&' | b'<' | b'>' | b'"'))
+    {
+        let mut string = String::with_capacity(str.len() + 16);
+        escape(str, &mut string).expect("writing to a String can't fail");
+        Cow::Owned(string)
+    } else {
+        Cow::Borrowed(str)
+    }
+}
+
+/// Decodes numeric character references and the named references known to
+/// [`crate::entity::lookup`]. Anything which isn't a complete reference, including references
+/// without a trailing `;`, is kept as is. Numeric references which aren't a valid `char` become
+/// U+FFFD.
+pub fn unescape(str: &str) -> Cow<'_, str> {
+    if !str.contains('&') {
+        return Cow::Borrowed(str);
+    }
+    let mut string = String::with_capacity(str.len());
+    let mut rest = str;
+    while let Some(start) = rest.find('&') {
+        string.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match reference(&rest[1..]) {
+            Some((char, len)) => {
+                string.push(char);
+                rest = &rest[1 + len..];
+            }
+            None => {
+                string.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    string.push_str(rest);
+    Cow::Owned(string)
+}
+
+// Parses a reference after its `&`, returning the char and the length of the reference.
+fn reference(str: &str) -> Option<(char, usize)> {
+    let end = str.find(';')?;
+    Some((decode_reference(&str[..end])?, end + 1))
+}
+
+// Decodes the name of a reference, i.e. the part between `&` and `;`.
+pub(crate) fn decode_reference(name: &str) -> Option<char> {
+    match name.strip_prefix('#') {
+        Some(number) => {
+            let (digits, radix) = match number.strip_prefix(&['x', 'X'][..]) {
+                Some(hex) => (hex, 16),
+                None => (number, 10),
+            };
+            if digits.is_empty() || !digits.chars().all(|char| char.is_digit(radix)) {
+                return None;
+            }
+            Some(
+                u32::from_str_radix(digits, radix)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .filter(|&char| char != '\0')
+                    .unwrap_or(char::REPLACEMENT_CHARACTER),
+            )
+        }
+        None => {
+            if name.is_empty() || !name.chars().all(|char| char.is_ascii_alphanumeric()) {
+                return None;
+            }
+            crate::entity::lookup(name)
+        }
+    }
+}
+
 #[test]
 fn test() {
     t("", "");
@@ -85,3 +281,210 @@ fn test_arguments() {
         assert_eq!(string, output);
     }
 }
+
+#[test]
+fn test_writer_errors() {
+    use std::fmt::Write;
+
+    // Fails the write after `writes` successful ones.
+    struct Failing {
+        output: String,
+        writes: usize,
+    }
+
+    impl std::fmt::Write for Failing {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            if self.writes == 0 {
+                return Err(std::fmt::Error);
+            }
+            self.writes -= 1;
+            self.output.push_str(s);
+            Ok(())
+        }
+    }
+
+    let (a, b, c, d) = ("a≤", "<≥", '&', "é\"");
+    let expected = "a≤&lt;≥&amp;é&quot;";
+    for writes in (0..20).chain([usize::MAX]) {
+        let mut writer = Failing {
+            output: String::new(),
+            writes,
+        };
+        let result = write!(Escape(&mut writer), "{}{}{}{}", a, b, c, d);
+        // The output is cut between escaped characters, never inside one.
+        assert!(expected.starts_with(&writer.output), "{}", writer.output);
+        if writes == usize::MAX {
+            assert!(result.is_ok());
+        }
+        if result.is_ok() {
+            assert_eq!(writer.output, expected);
+        }
+        // A cut output must come with the inner writer's error.
+        if writer.output.len() < expected.len() {
+            assert!(result.is_err(), "{}", writer.output);
+        }
+    }
+}
+
+#[test]
+fn test_attribute() {
+    t("", "");
+    t("<", "&lt;");
+    t("a<", "a&lt;");
+    t("<b", "&lt;b");
+    t("a<b", "a&lt;b");
+    t("a<>b", "a&lt;&gt;b");
+    t("<>", "&lt;&gt;");
+    t("≤", "≤");
+    t("a≤", "a≤");
+    t("≤b", "≤b");
+    t("a≤b", "a≤b");
+    t("a≤≥b", "a≤≥b");
+    t("≤≥", "≤≥");
+    t(
+        r#"foo &<>" bar&bar<bar>bar"bar baz&&<<baz>>""baz"#,
+        r#"foo &amp;&lt;&gt;&quot; bar&amp;bar&lt;bar&gt;bar&quot;bar baz&amp;&amp;&lt;&lt;baz&gt;&gt;&quot;&quot;baz"#,
+    );
+    t("'", "&#39;");
+    t(r#"a'"&b"#, "a&#39;&quot;&amp;b");
+    t(
+        r#"'' onmouseover="x"'"#,
+        "&#39;&#39; onmouseover=&quot;x&quot;&#39;",
+    );
+
+    fn t(input: &str, output: &str) {
+        let mut string = String::new();
+        escape_attribute(input, &mut string).unwrap();
+        assert_eq!(string, output);
+    }
+}
+
+#[test]
+fn test_attribute_arguments() {
+    use std::fmt::Write;
+
+    t("", "&quot;&quot;");
+    t("<", "&quot;&lt;&quot;");
+    t("a<", "&quot;a&lt;&quot;");
+    t("<b", "&quot;&lt;b&quot;");
+    t("a<b", "&quot;a&lt;b&quot;");
+    t("a<>b", "&quot;a&lt;&gt;b&quot;");
+    t("<>", "&quot;&lt;&gt;&quot;");
+    t("≤", "&quot;≤&quot;");
+    t("a≤b", "&quot;a≤b&quot;");
+    t(
+        r#"foo &<>" bar&bar<bar>bar"bar baz&&<<baz>>""baz"#,
+        r#"&quot;foo &amp;&lt;&gt;\&quot; bar&amp;bar&lt;bar&gt;bar\&quot;bar baz&amp;&amp;&lt;&lt;baz&gt;&gt;\&quot;\&quot;baz&quot;"#,
+    );
+    t('<', "&#39;&lt;&#39;");
+    t(r#"a'"&b"#, r#"&quot;a&#39;\&quot;&amp;b&quot;"#);
+
+    fn t(input: impl std::fmt::Debug, output: &str) {
+        let mut string = String::new();
+        write!(
+            EscapeAttribute(&mut string),
+            "{}",
+            format_args!("{:?}", input)
+        )
+        .unwrap();
+        assert_eq!(string, output);
+    }
+}
+
+#[test]
+fn test_apostrophes() {
+    use std::fmt::Write;
+
+    t("", "");
+    t("'", "&#39;");
+    t("a'b''c", "a&#39;b&#39;&#39;c");
+    t("&amp;&quot;", "&amp;&quot;");
+
+    fn t(input: &str, output: &str) {
+        let mut string = String::new();
+        Apostrophes(&mut string).write_str(input).unwrap();
+        assert_eq!(string, output);
+    }
+}
+
+// A fixed pseudorandom sequence, so failures of the randomized tests are reproducible.
+#[cfg(test)]
+fn xorshift(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+#[cfg(feature = "memchr")]
+#[test]
+fn test_memchr() {
+    const CHARS: &[char] = &['a', ' ', '&', '<', '>', '"', '\'', 'é', '≤', '😀'];
+
+    let mut state = 0x9e37_79b9_7f4a_7c15_u64;
+    let mut next = || xorshift(&mut state);
+    for _ in 0..5000 {
+        let len = next() % 200;
+        // Anything from text with no special characters to text with nothing else.
+        let density = next() % 5;
+        let string = (0..len)
+            .map(|_| match next() % 8 < density {
+                true => CHARS[(next() % CHARS.len() as u64) as usize],
+                false => 'x',
+            })
+            .collect::<String>();
+        let (mut scalar, mut memchr) = (String::new(), String::new());
+        escape_scalar::<_, false>(&string, &mut scalar).unwrap();
+        escape_memchr::<_, false>(&string, &mut memchr).unwrap();
+        assert_eq!(scalar, memchr, "{:?}", string);
+        let (mut scalar, mut memchr) = (String::new(), String::new());
+        escape_scalar::<_, true>(&string, &mut scalar).unwrap();
+        escape_memchr::<_, true>(&string, &mut memchr).unwrap();
+        assert_eq!(scalar, memchr, "{:?}", string);
+    }
+}
+
+#[test]
+fn test_unescape() {
+    t("", "");
+    t("no references", "no references");
+    t("&amp;&lt;&gt;&quot;&#39;&apos;", "&<>\"''");
+    t("a&#x27;b&#X27;c&#8212;d", "a'b'c—d");
+    t("&#x1F600; &#128512;", "😀 😀");
+    t("&mdash;&hellip;&nbsp;&rarr;", "—…\u{a0}→");
+    t("≤&lt;≥", "≤<≥");
+    t(
+        "&#x110000;&#xD800;&#0;&#99999999999;",
+        "\u{fffd}\u{fffd}\u{fffd}\u{fffd}",
+    );
+    t(
+        "& &; &amp &#; &#x; &#xZZ; &#12a; &unknown; &a-b;",
+        "& &; &amp &#; &#x; &#xZZ; &#12a; &unknown; &a-b;",
+    );
+    t("&&amp;;", "&&;");
+    t("trailing &", "trailing &");
+
+    assert!(matches!(unescape("plain"), Cow::Borrowed("plain")));
+
+    fn t(input: &str, output: &str) {
+        assert_eq!(unescape(input), output);
+    }
+}
+
+#[test]
+fn test_unescape_roundtrip() {
+    const CHARS: &[char] = &[
+        'a', ' ', '&', '<', '>', '"', '\'', ';', '#', 'x', '1', 'é', '≤', '😀',
+    ];
+
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut next = || xorshift(&mut state);
+    for _ in 0..2000 {
+        let len = next() % 24;
+        let string = (0..len)
+            .map(|_| CHARS[(next() % CHARS.len() as u64) as usize])
+            .collect::<String>();
+        assert_eq!(unescape(&escape_str(&string)), string);
+    }
+    assert!(matches!(escape_str("plain"), Cow::Borrowed("plain")));
+}
@@ -0,0 +1,227 @@
+//! Builders for Atom feeds.
+//!
+//! Required elements are arguments of the constructors, everything else is optional.
+
+use crate::escape::Escape;
+use crate::{DynRender, Render, RenderError};
+use std::borrow::Cow;
+
+/// An RFC 3339 timestamp, as used by `<updated>` and `<published>`. Strings are written as is.
+pub trait Timestamp {
+    fn rfc3339(&self) -> Cow<'_, str>;
+}
+
+impl Timestamp for str {
+    fn rfc3339(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl Timestamp for String {
+    fn rfc3339(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<T: Timestamp + ?Sized> Timestamp for &T {
+    fn rfc3339(&self) -> Cow<'_, str> {
+        T::rfc3339(self)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> Timestamp for chrono::DateTime<Tz>
+where
+    Tz::Offset: std::fmt::Display,
+{
+    fn rfc3339(&self) -> Cow<'_, str> {
+        Cow::Owned(self.to_rfc3339())
+    }
+}
+
+pub(crate) fn element(
+    writer: &mut impl std::fmt::Write,
+    name: &str,
+    content: &str,
+) -> Result<(), RenderError> {
+    write!(writer, "<{}>", name)?;
+    content.render(writer)?;
+    write!(writer, "</{}>", name)?;
+    Ok(())
+}
+
+fn link(writer: &mut impl std::fmt::Write, href: &str, rel: &str) -> Result<(), RenderError> {
+    writer.write_str("<link href=\"")?;
+    href.render(writer)?;
+    write!(writer, "\" rel=\"{}\"/>", rel)?;
+    Ok(())
+}
+
+pub struct Atom<'a> {
+    title: Cow<'a, str>,
+    id: Cow<'a, str>,
+    updated: String,
+    subtitle: Option<Cow<'a, str>>,
+    links: Vec<(Cow<'a, str>, &'static str)>,
+    authors: Vec<Cow<'a, str>>,
+    entries: Vec<Entry<'a>>,
+}
+
+impl<'a> Atom<'a> {
+    pub fn new(
+        title: impl Into<Cow<'a, str>>,
+        id: impl Into<Cow<'a, str>>,
+        updated: impl Timestamp,
+    ) -> Self {
+        Atom {
+            title: title.into(),
+            id: id.into(),
+            updated: updated.rfc3339().into_owned(),
+            subtitle: None,
+            links: Vec::new(),
+            authors: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn subtitle(mut self, subtitle: impl Into<Cow<'a, str>>) -> Self {
+        self.subtitle = Some(subtitle.into());
+        self
+    }
+
+    /// Adds a `rel="alternate"` link, usually to the web page of the feed.
+    pub fn link(mut self, href: impl Into<Cow<'a, str>>) -> Self {
+        self.links.push((href.into(), "alternate"));
+        self
+    }
+
+    /// Adds a `rel="self"` link to the feed itself.
+    pub fn self_link(mut self, href: impl Into<Cow<'a, str>>) -> Self {
+        self.links.push((href.into(), "self"));
+        self
+    }
+
+    pub fn author(mut self, name: impl Into<Cow<'a, str>>) -> Self {
+        self.authors.push(name.into());
+        self
+    }
+
+    pub fn entry(mut self, entry: Entry<'a>) -> Self {
+        self.entries.push(entry);
+        self
+    }
+}
+
+impl Render for Atom<'_> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        writer.write_str(r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+        writer.write_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#)?;
+        element(writer, "title", &self.title)?;
+        if let Some(subtitle) = &self.subtitle {
+            element(writer, "subtitle", subtitle)?;
+        }
+        element(writer, "id", &self.id)?;
+        element(writer, "updated", &self.updated)?;
+        for (href, rel) in &self.links {
+            link(writer, href, rel)?;
+        }
+        for author in &self.authors {
+            writer.write_str("<author>")?;
+            element(writer, "name", author)?;
+            writer.write_str("</author>")?;
+        }
+        for entry in &self.entries {
+            entry.render(writer)?;
+        }
+        writer.write_str("</feed>")?;
+        Ok(())
+    }
+}
+
+pub struct Entry<'a> {
+    title: Cow<'a, str>,
+    id: Cow<'a, str>,
+    updated: String,
+    published: Option<String>,
+    links: Vec<Cow<'a, str>>,
+    authors: Vec<Cow<'a, str>>,
+    summary: Option<Cow<'a, str>>,
+    content: Option<DynRender<'a>>,
+}
+
+impl<'a> Entry<'a> {
+    pub fn new(
+        title: impl Into<Cow<'a, str>>,
+        id: impl Into<Cow<'a, str>>,
+        updated: impl Timestamp,
+    ) -> Self {
+        Entry {
+            title: title.into(),
+            id: id.into(),
+            updated: updated.rfc3339().into_owned(),
+            published: None,
+            links: Vec::new(),
+            authors: Vec::new(),
+            summary: None,
+            content: None,
+        }
+    }
+
+    pub fn published(mut self, published: impl Timestamp) -> Self {
+        self.published = Some(published.rfc3339().into_owned());
+        self
+    }
+
+    /// Adds a `rel="alternate"` link, usually to the web page of the entry.
+    pub fn link(mut self, href: impl Into<Cow<'a, str>>) -> Self {
+        self.links.push(href.into());
+        self
+    }
+
+    pub fn author(mut self, name: impl Into<Cow<'a, str>>) -> Self {
+        self.authors.push(name.into());
+        self
+    }
+
+    pub fn summary(mut self, summary: impl Into<Cow<'a, str>>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    /// Sets an HTML fragment as the content. The fragment is rendered when the feed is rendered,
+    /// and written escaped inside `<content type="html">`.
+    pub fn content_html(mut self, fragment: impl Render + 'a) -> Self {
+        self.content = Some(crate::new(move |mut writer| fragment.render(&mut writer)));
+        self
+    }
+}
+
+impl Render for Entry<'_> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        writer.write_str("<entry>")?;
+        element(writer, "title", &self.title)?;
+        element(writer, "id", &self.id)?;
+        element(writer, "updated", &self.updated)?;
+        if let Some(published) = &self.published {
+            element(writer, "published", published)?;
+        }
+        for href in &self.links {
+            link(writer, href, "alternate")?;
+        }
+        for author in &self.authors {
+            writer.write_str("<author>")?;
+            element(writer, "name", author)?;
+            writer.write_str("</author>")?;
+        }
+        if let Some(summary) = &self.summary {
+            element(writer, "summary", summary)?;
+        }
+        if let Some(content) = &self.content {
+            writer.write_str(r#"<content type="html">"#)?;
+            content.render(&mut Escape(writer))?;
+            writer.write_str("</content>")?;
+        }
+        writer.write_str("</entry>")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+//! Rendering from and into the fixed capacity strings of `heapless` and `arrayvec`.
+
+use crate::{Render, RenderAttributeValue, RenderError};
+
+/// The error returned when the output of a template doesn't fit in a fixed capacity string.
+#[derive(Debug, PartialEq)]
+pub struct CapacityExceeded {
+    pub capacity: usize,
+}
+
+impl std::fmt::Display for CapacityExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "output exceeds the capacity of {} bytes", self.capacity)
+    }
+}
+
+impl std::error::Error for CapacityExceeded {}
+
+// Both string types fail a write which doesn't fit, which is the only way they fail.
+struct Fixed<'a, W> {
+    inner: &'a mut W,
+    full: bool,
+}
+
+impl<W: std::fmt::Write> std::fmt::Write for Fixed<'_, W> {
+    fn write_str(&mut self, str: &str) -> std::fmt::Result {
+        let result = self.inner.write_str(str);
+        self.full |= result.is_err();
+        result
+    }
+}
+
+fn render_fixed<W: std::fmt::Write>(
+    template: impl Render,
+    inner: &mut W,
+    capacity: usize,
+) -> Result<(), RenderError> {
+    let mut writer = Fixed { inner, full: false };
+    let result = template.render(&mut writer);
+    if writer.full {
+        return Err(RenderError::wrap(CapacityExceeded { capacity }));
+    }
+    result
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> Render for heapless::String<N> {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        self.as_str().render(writer)
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> RenderAttributeValue for heapless::String<N> {}
+
+/// The bytes are written as is, without escaping, and must be valid UTF-8.
+#[cfg(feature = "heapless")]
+impl<const N: usize> Render for heapless::Vec<u8, N> {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        let str = std::str::from_utf8(self).map_err(RenderError::wrap)?;
+        Ok(writer.write_str(str)?)
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> RenderAttributeValue for heapless::Vec<u8, N> {}
+
+/// Renders `template` into a `heapless::String<N>`, failing with [`CapacityExceeded`] if the
+/// output is longer than `N` bytes.
+///
+/// The `heapless` feature needs Rust 1.60 or later, since the manifest of heapless 0.8 uses
+/// `dep:` features.
+#[cfg(feature = "heapless")]
+pub fn render_to_heapless<const N: usize>(
+    template: impl Render,
+) -> Result<heapless::String<N>, RenderError> {
+    let mut string = heapless::String::new();
+    render_fixed(template, &mut string, N)?;
+    Ok(string)
+}
+
+#[cfg(feature = "arrayvec")]
+impl<const N: usize> Render for arrayvec::ArrayString<N> {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        self.as_str().render(writer)
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<const N: usize> RenderAttributeValue for arrayvec::ArrayString<N> {}
+
+/// Renders `template` into an `arrayvec::ArrayString<N>`, failing with [`CapacityExceeded`] if
+/// the output is longer than `N` bytes.
+#[cfg(feature = "arrayvec")]
+pub fn render_to_array_string<const N: usize>(
+    template: impl Render,
+) -> Result<arrayvec::ArrayString<N>, RenderError> {
+    let mut string = arrayvec::ArrayString::new();
+    render_fixed(template, &mut string, N)?;
+    Ok(string)
+}
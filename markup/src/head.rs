@@ -0,0 +1,138 @@
+//! Builders for `<link>` elements commonly found in `<head>`.
+
+use crate::{Render, RenderError};
+use std::borrow::Cow;
+
+/// The `as` attribute of a [`preload`] link.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum As {
+    Audio,
+    Document,
+    Embed,
+    Fetch,
+    Font,
+    Image,
+    Object,
+    Script,
+    Style,
+    Track,
+    Video,
+    Worker,
+}
+
+impl As {
+    fn as_str(self) -> &'static str {
+        match self {
+            As::Audio => "audio",
+            As::Document => "document",
+            As::Embed => "embed",
+            As::Fetch => "fetch",
+            As::Font => "font",
+            As::Image => "image",
+            As::Object => "object",
+            As::Script => "script",
+            As::Style => "style",
+            As::Track => "track",
+            As::Video => "video",
+            As::Worker => "worker",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Link<'a> {
+    rel: &'static str,
+    href: Cow<'a, str>,
+    as_: Option<As>,
+    mime: Option<Cow<'a, str>>,
+    sizes: Option<Cow<'a, str>>,
+    crossorigin: bool,
+}
+
+impl<'a> Link<'a> {
+    fn new(rel: &'static str, href: impl Into<Cow<'a, str>>) -> Self {
+        Link {
+            rel,
+            href: href.into(),
+            as_: None,
+            mime: None,
+            sizes: None,
+            crossorigin: false,
+        }
+    }
+
+    /// Adds a bare `crossorigin` attribute, i.e. an anonymous CORS request.
+    pub fn crossorigin(mut self) -> Self {
+        self.crossorigin = true;
+        self
+    }
+
+    /// Sets the `type` attribute.
+    pub fn mime(mut self, mime: impl Into<Cow<'a, str>>) -> Self {
+        self.mime = Some(mime.into());
+        self
+    }
+}
+
+pub fn stylesheet<'a>(href: impl Into<Cow<'a, str>>) -> Link<'a> {
+    Link::new("stylesheet", href)
+}
+
+/// Fonts are always fetched in CORS mode, so preloading one also adds `crossorigin`.
+pub fn preload<'a>(href: impl Into<Cow<'a, str>>, as_: As) -> Link<'a> {
+    Link {
+        as_: Some(as_),
+        crossorigin: as_ == As::Font,
+        ..Link::new("preload", href)
+    }
+}
+
+pub fn prefetch<'a>(href: impl Into<Cow<'a, str>>) -> Link<'a> {
+    Link::new("prefetch", href)
+}
+
+pub fn preconnect<'a>(origin: impl Into<Cow<'a, str>>) -> Link<'a> {
+    Link::new("preconnect", origin)
+}
+
+pub fn icon<'a>(
+    href: impl Into<Cow<'a, str>>,
+    sizes: impl Into<Cow<'a, str>>,
+    mime: impl Into<Cow<'a, str>>,
+) -> Link<'a> {
+    Link {
+        sizes: Some(sizes.into()),
+        mime: Some(mime.into()),
+        ..Link::new("icon", href)
+    }
+}
+
+pub fn canonical<'a>(url: impl Into<Cow<'a, str>>) -> Link<'a> {
+    Link::new("canonical", url)
+}
+
+impl Render for Link<'_> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        write!(writer, "<link rel=\"{}\" href=\"", self.rel)?;
+        self.href.render(writer)?;
+        writer.write_str("\"")?;
+        if let Some(as_) = self.as_ {
+            write!(writer, " as=\"{}\"", as_.as_str())?;
+        }
+        if let Some(mime) = &self.mime {
+            writer.write_str(" type=\"")?;
+            mime.render(writer)?;
+            writer.write_str("\"")?;
+        }
+        if let Some(sizes) = &self.sizes {
+            writer.write_str(" sizes=\"")?;
+            sizes.render(writer)?;
+            writer.write_str("\"")?;
+        }
+        if self.crossorigin {
+            writer.write_str(" crossorigin")?;
+        }
+        writer.write_str(">")?;
+        Ok(())
+    }
+}
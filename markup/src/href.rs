@@ -0,0 +1,99 @@
+use crate::{Render, RenderAttributeValue, RenderError};
+
+/// A value which can be written as a URL, e.g. an enum of the routes of an application.
+///
+/// Strings are written as they are. Use [`Segment`] for values interpolated into a path or a
+/// query, which percent-encodes them:
+///
+/// ```rust
+/// use markup::{RenderError, Segment, ToHref};
+///
+/// enum Route<'a> {
+///     Home,
+///     User { name: &'a str },
+/// }
+///
+/// impl ToHref for Route<'_> {
+///     fn write_href(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+///         match self {
+///             Route::Home => "/".write_href(writer),
+///             Route::User { name } => {
+///                 "/users/".write_href(writer)?;
+///                 Segment(name).write_href(writer)
+///             }
+///         }
+///     }
+/// }
+///
+/// let template = markup::new!(a[href = markup::href(Route::User { name: "a b" })] { "A B" });
+/// assert_eq!(template.to_string(), r#"<a href="/users/a%20b">A B</a>"#);
+/// ```
+pub trait ToHref {
+    fn write_href(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError>;
+}
+
+impl<T: ToHref + ?Sized> ToHref for &T {
+    #[inline]
+    fn write_href(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        T::write_href(self, writer)
+    }
+}
+
+impl ToHref for str {
+    #[inline]
+    fn write_href(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        Ok(writer.write_str(self)?)
+    }
+}
+
+impl ToHref for String {
+    #[inline]
+    fn write_href(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        self.as_str().write_href(writer)
+    }
+}
+
+#[cfg(feature = "url")]
+impl ToHref for url::Url {
+    #[inline]
+    fn write_href(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        self.as_str().write_href(writer)
+    }
+}
+
+/// Percent-encodes every byte except ASCII letters, digits, `-`, `.`, `_` and `~`.
+pub struct Segment<'a>(pub &'a str);
+
+impl ToHref for Segment<'_> {
+    fn write_href(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        let mut last = 0;
+        for (index, byte) in self.0.bytes().enumerate() {
+            if !(byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~')) {
+                // `last` is only inside a character right before one of its continuation bytes.
+                if last < index {
+                    writer.write_str(&self.0[last..index])?;
+                }
+                write!(writer, "%{:02X}", byte)?;
+                last = index + 1;
+            }
+        }
+        writer.write_str(&self.0[last..])?;
+        Ok(())
+    }
+}
+
+pub struct Href<T>(T);
+
+/// Renders `value` as a URL, HTML-escaped, for use in `href` and `src` attributes.
+pub fn href<T: ToHref>(value: T) -> Href<T> {
+    Href(value)
+}
+
+impl<T: ToHref> Render for Href<T> {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        self.0.write_href(&mut crate::escape::Escape(writer))
+    }
+}
+
+impl<T: ToHref> RenderAttributeValue for Href<T> {}
@@ -0,0 +1,34 @@
+use crate::Render;
+
+/// Wraps a template to return it from an `axum` handler, enabled by the `axum` feature.
+///
+/// The response has `Content-Type: text/html; charset=utf-8` and the rendered template as its
+/// body. If rendering fails, the error is logged and an empty `500 Internal Server Error`
+/// response is returned instead, so no details of the error reach the client. Errors are logged
+/// with `tracing` if the `tracing` feature is enabled, and to stderr otherwise.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Html<T>(pub T);
+
+impl<T: Render> axum::response::IntoResponse for Html<T> {
+    fn into_response(self) -> axum::response::Response {
+        use axum::http::{header, HeaderValue, StatusCode};
+
+        match self.0.render_to_vec() {
+            Ok(body) => (
+                [(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("text/html; charset=utf-8"),
+                )],
+                body,
+            )
+                .into_response(),
+            Err(error) => {
+                #[cfg(feature = "tracing")]
+                crate::tracing::error!(%error, "markup: rendering the response failed");
+                #[cfg(not(feature = "tracing"))]
+                eprintln!("markup: rendering the response failed: {}", error);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
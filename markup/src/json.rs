@@ -0,0 +1,67 @@
+use crate::RenderError;
+use std::io;
+
+/// Serializes `value` as JSON into `writer`, escaping `<`, `>`, `&`, U+2028 and U+2029 as `\uXXXX`
+/// so the output is safe to embed inside a `<script>` element and still parses as the same JSON.
+pub(crate) fn write<T: serde::Serialize + ?Sized>(
+    writer: &mut impl std::fmt::Write,
+    value: &T,
+) -> Result<(), RenderError> {
+    let mut writer = Writer {
+        inner: writer,
+        pending: Vec::new(),
+    };
+    match serde_json_crate::to_writer(&mut writer, value) {
+        Ok(()) => Ok(()),
+        // The only I/O errors come from the underlying `fmt::Write`.
+        Err(error) if error.is_io() => Err(RenderError::Fmt(std::fmt::Error)),
+        Err(error) => Err(RenderError::wrap(error)),
+    }
+}
+
+struct Writer<'a, W> {
+    inner: &'a mut W,
+    // Trailing bytes of a UTF-8 sequence split across two writes.
+    pending: Vec<u8>,
+}
+
+impl<W: std::fmt::Write> Writer<'_, W> {
+    fn escape(&mut self, str: &str) -> std::fmt::Result {
+        let mut last = 0;
+        for (index, char) in str.char_indices() {
+            let escaped = match char {
+                '<' => "\\u003c",
+                '>' => "\\u003e",
+                '&' => "\\u0026",
+                '\u{2028}' => "\\u2028",
+                '\u{2029}' => "\\u2029",
+                _ => continue,
+            };
+            self.inner.write_str(&str[last..index])?;
+            self.inner.write_str(escaped)?;
+            last = index + char.len_utf8();
+        }
+        self.inner.write_str(&str[last..])
+    }
+}
+
+impl<W: std::fmt::Write> io::Write for Writer<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut bytes = std::mem::take(&mut self.pending);
+        bytes.extend_from_slice(buf);
+        let valid = match std::str::from_utf8(&bytes) {
+            Ok(str) => str.len(),
+            Err(error) if error.error_len().is_none() => error.valid_up_to(),
+            Err(error) => return Err(io::Error::new(io::ErrorKind::InvalidData, error)),
+        };
+        let str = std::str::from_utf8(&bytes[..valid]).expect("validated above");
+        self.escape(str)
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+        self.pending = bytes[valid..].to_vec();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -1,11 +1,113 @@
 use std::fmt::Write;
 
-pub use markup_proc_macro::{define, new};
-
-mod escape;
+pub use markup_proc_macro::{define, define_from_file, new, render, render_impl};
+
+mod attr_map;
+mod buffer;
+pub mod cache;
+mod counting;
+mod deadline;
+#[cfg(feature = "debug-comments")]
+#[doc(hidden)]
+pub mod debug_comments;
+mod deferred;
+#[cfg(feature = "dev-reload")]
+pub mod dev_reload;
+#[cfg(feature = "encoding")]
+mod encoding;
+pub mod entity;
+mod error;
+pub mod escape;
+pub mod feed;
+#[cfg(any(feature = "heapless", feature = "arrayvec"))]
+mod fixed;
+pub mod head;
+mod href;
+#[cfg(feature = "axum")]
+mod integrations;
+#[cfg(feature = "serde_json")]
+mod json;
+pub mod meta;
+mod plain_text;
+mod pretty;
+mod reader;
+pub mod registry;
+mod select;
+pub mod sitemap;
+mod stream;
+pub mod suspense;
+pub mod table;
+#[cfg(feature = "httpdate")]
+mod time;
+mod tokens;
+
+pub use attr_map::{AttrMap, AttrValue};
+pub use buffer::Buffer;
+pub use counting::{tee, CountingWriter, Tee};
+pub use deadline::{render_with_deadline, DeadlineWriter, RenderDeadlineExceeded};
+pub use deferred::{Deferred, Flush};
+#[cfg(feature = "encoding")]
+pub use encoding::{render_encoded, EncodingWriter};
+pub use error::RenderError;
+pub use escape::{escape_attribute, EscapeAttribute};
+#[cfg(feature = "arrayvec")]
+pub use fixed::render_to_array_string;
+#[cfg(feature = "heapless")]
+pub use fixed::render_to_heapless;
+#[cfg(any(feature = "heapless", feature = "arrayvec"))]
+pub use fixed::CapacityExceeded;
+pub use href::{href, Href, Segment, ToHref};
+#[cfg(feature = "axum")]
+pub use integrations::Html;
+pub use plain_text::{to_plain_text, PlainTextWriter};
+pub use pretty::{pretty, Pretty, PrettyWriter};
+pub use reader::RenderReader;
+pub use select::{select, Select};
+pub use stream::{stream, RenderStream};
+pub use suspense::suspense;
+#[cfg(feature = "httpdate")]
+pub use time::{httpdate, rfc3339, HttpDate, Rfc3339, YearOutOfRange};
+pub use tokens::{tokens, InvalidToken, Token, Tokens};
+
+#[cfg(feature = "tracing")]
+#[doc(hidden)]
+pub use tracing_crate as tracing;
 
 pub trait Render {
-    fn render(&self, writer: &mut impl std::fmt::Write) -> std::fmt::Result;
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError>;
+
+    /// A rough estimate of the length of the output, used to preallocate buffers. For
+    /// `define!` templates it is the total length of the template's static output.
+    #[inline]
+    fn size_hint(&self) -> usize {
+        0
+    }
+
+    fn render_to_string(&self) -> Result<String, RenderError> {
+        let mut string = String::with_capacity(self.size_hint());
+        self.render(&mut string)?;
+        Ok(string)
+    }
+
+    fn render_to_vec(&self) -> Result<Vec<u8>, RenderError> {
+        Ok(self.render_to_string()?.into_bytes())
+    }
+
+    /// The exact length of the output in bytes, computed by rendering into a
+    /// [`CountingWriter`].
+    fn rendered_len(&self) -> Result<u64, RenderError> {
+        let mut writer = CountingWriter::new();
+        self.render(&mut writer)?;
+        Ok(writer.count())
+    }
+
+    /// Renders to an [`std::io::Write`] in chunks of up to 8 KiB, instead of a write for every
+    /// piece of output, and flushes it at the end. Errors of the writer are returned as a
+    /// [`RenderError`] whose source is the [`std::io::Error`]. Output written before an error may
+    /// not have reached the writer.
+    fn render_buffered(&self, writer: &mut impl std::io::Write) -> Result<(), RenderError> {
+        buffer::render_buffered(self, writer)
+    }
 }
 
 pub trait RenderAttributeValue: Render {
@@ -25,14 +127,19 @@ pub trait RenderAttributeValue: Render {
     }
 }
 
-impl<'a, T: Render + ?Sized> Render for &'a T {
+impl<T: Render + ?Sized> Render for &T {
     #[inline]
-    fn render(&self, writer: &mut impl std::fmt::Write) -> std::fmt::Result {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
         T::render(self, writer)
     }
+
+    #[inline]
+    fn size_hint(&self) -> usize {
+        T::size_hint(self)
+    }
 }
 
-impl<'a, T: RenderAttributeValue + ?Sized> RenderAttributeValue for &'a T {
+impl<T: RenderAttributeValue + ?Sized> RenderAttributeValue for &T {
     #[inline]
     fn is_none(&self) -> bool {
         T::is_none(self)
@@ -51,9 +158,14 @@ impl<'a, T: RenderAttributeValue + ?Sized> RenderAttributeValue for &'a T {
 
 impl<T: Render + ?Sized> Render for Box<T> {
     #[inline]
-    fn render(&self, writer: &mut impl std::fmt::Write) -> std::fmt::Result {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
         T::render(self, writer)
     }
+
+    #[inline]
+    fn size_hint(&self) -> usize {
+        T::size_hint(self)
+    }
 }
 
 impl<T: RenderAttributeValue + ?Sized> RenderAttributeValue for Box<T> {
@@ -73,10 +185,68 @@ impl<T: RenderAttributeValue + ?Sized> RenderAttributeValue for Box<T> {
     }
 }
 
+impl<T: Render + ?Sized> Render for std::rc::Rc<T> {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        T::render(self, writer)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> usize {
+        T::size_hint(self)
+    }
+}
+
+impl<T: RenderAttributeValue + ?Sized> RenderAttributeValue for std::rc::Rc<T> {
+    #[inline]
+    fn is_none(&self) -> bool {
+        T::is_none(self)
+    }
+
+    #[inline]
+    fn is_true(&self) -> bool {
+        T::is_true(self)
+    }
+
+    #[inline]
+    fn is_false(&self) -> bool {
+        T::is_false(self)
+    }
+}
+
+impl<T: Render + ?Sized> Render for std::sync::Arc<T> {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        T::render(self, writer)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> usize {
+        T::size_hint(self)
+    }
+}
+
+impl<T: RenderAttributeValue + ?Sized> RenderAttributeValue for std::sync::Arc<T> {
+    #[inline]
+    fn is_none(&self) -> bool {
+        T::is_none(self)
+    }
+
+    #[inline]
+    fn is_true(&self) -> bool {
+        T::is_true(self)
+    }
+
+    #[inline]
+    fn is_false(&self) -> bool {
+        T::is_false(self)
+    }
+}
+
 impl Render for bool {
     #[inline]
-    fn render(&self, writer: &mut impl std::fmt::Write) -> std::fmt::Result {
-        write!(writer, "{}", self)
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        Ok(write!(writer, "{}", self)?)
     }
 }
 
@@ -94,7 +264,7 @@ impl RenderAttributeValue for bool {
 
 impl<T: Render> Render for Option<T> {
     #[inline]
-    fn render(&self, writer: &mut impl std::fmt::Write) -> std::fmt::Result {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
         match self {
             Some(t) => t.render(writer),
             None => Ok(()),
@@ -109,24 +279,235 @@ impl<T: RenderAttributeValue> RenderAttributeValue for Option<T> {
     }
 }
 
+/// An `Err` fails the render with the error. The result is borrowed, so the error is shared in an
+/// `Arc`, which `RenderError::downcast_ref::<Arc<E>>()` returns. `@expr?` propagates an error of
+/// any other type.
+impl<T, E> Render for Result<T, std::sync::Arc<E>>
+where
+    T: Render,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        match self {
+            Ok(t) => t.render(writer),
+            Err(error) => Err(RenderError::wrap(std::sync::Arc::clone(error))),
+        }
+    }
+}
+
+impl<T, E> RenderAttributeValue for Result<T, std::sync::Arc<E>>
+where
+    T: RenderAttributeValue,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    #[inline]
+    fn is_none(&self) -> bool {
+        matches!(self, Ok(t) if t.is_none())
+    }
+
+    #[inline]
+    fn is_true(&self) -> bool {
+        matches!(self, Ok(t) if t.is_true())
+    }
+
+    #[inline]
+    fn is_false(&self) -> bool {
+        matches!(self, Ok(t) if t.is_false())
+    }
+}
+
+impl<T: Render> Render for [T] {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        self.iter().try_for_each(|item| item.render(writer))
+    }
+
+    fn size_hint(&self) -> usize {
+        self.iter().map(T::size_hint).sum()
+    }
+}
+
+impl<T: Render, const N: usize> Render for [T; N] {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        self[..].render(writer)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> usize {
+        self[..].size_hint()
+    }
+}
+
+impl<T: Render> Render for Vec<T> {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        self[..].render(writer)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> usize {
+        self[..].size_hint()
+    }
+}
+
 struct Raw<T: std::fmt::Display>(T);
 
 impl<T: std::fmt::Display> Render for Raw<T> {
     #[inline]
-    fn render(&self, writer: &mut impl std::fmt::Write) -> std::fmt::Result {
-        write!(writer, "{}", self.0)
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        Ok(write!(writer, "{}", self.0)?)
     }
 }
 
 impl<T: std::fmt::Display> RenderAttributeValue for Raw<T> {}
 
 #[inline]
-pub fn raw(value: impl std::fmt::Display) -> impl Render + RenderAttributeValue {
+pub fn raw(value: impl std::fmt::Display) -> impl RenderAttributeValue {
     Raw(value)
 }
 
+/// HTML which is rendered without escaping, both as text and as an attribute value. Unlike
+/// [`raw`], which takes anything, it can only be made from a string literal or by explicitly
+/// vouching for a string, so it is easy to spot where unescaped content comes from:
+///
+/// ```rust
+/// use markup::Trusted;
+///
+/// let icon = Trusted::from_static("<svg></svg>") + &Trusted::from_static("&nbsp;");
+/// let template = markup::new!(span[title = &icon] { @icon });
+/// assert_eq!(
+///     template.to_string(),
+///     "<span title=\"<svg></svg>&nbsp;\"><svg></svg>&nbsp;</span>"
+/// );
+///
+/// // Content built at run time has to go through `assert_trusted`.
+/// let name = String::from("<b>Ferris</b>");
+/// let bold = Trusted::assert_trusted(name);
+/// assert_eq!(markup::new!(@bold).to_string(), "<b>Ferris</b>");
+/// ```
+///
+/// `from_static` only takes `&'static str`, so runtime data such as a request parameter can't be
+/// passed to it:
+///
+/// ```compile_fail
+/// let input = String::from("<script>alert(1)</script>");
+/// let html = markup::Trusted::from_static(&input);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Trusted(std::borrow::Cow<'static, str>);
+
+impl Trusted {
+    #[inline]
+    pub const fn from_static(html: &'static str) -> Self {
+        Trusted(std::borrow::Cow::Borrowed(html))
+    }
+
+    /// Wraps `html` without any checks. The caller is responsible for it being safe to include
+    /// in a page as is; never pass user input which hasn't been sanitized.
+    #[inline]
+    pub fn assert_trusted(html: String) -> Self {
+        Trusted(std::borrow::Cow::Owned(html))
+    }
+
+    /// Appends another trusted fragment.
+    pub fn push(&mut self, other: &Trusted) {
+        self.0.to_mut().push_str(&other.0);
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    #[inline]
+    pub fn into_string(self) -> String {
+        self.0.into_owned()
+    }
+}
+
+impl std::ops::Add<&Trusted> for Trusted {
+    type Output = Trusted;
+
+    fn add(mut self, other: &Trusted) -> Trusted {
+        self.push(other);
+        self
+    }
+}
+
+impl std::ops::AddAssign<&Trusted> for Trusted {
+    fn add_assign(&mut self, other: &Trusted) {
+        self.push(other);
+    }
+}
+
+impl Render for Trusted {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        Ok(writer.write_str(&self.0)?)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl RenderAttributeValue for Trusted {}
+
+/// Collects `(name, value)` attributes sorted by their rendered name, for spreading attributes
+/// from a source without a stable order, e.g. `div[..markup::sorted(&map)]`.
+pub fn sorted<I, N, V>(attributes: I) -> Vec<(N, V)>
+where
+    I: IntoIterator<Item = (N, V)>,
+    N: Render,
+{
+    let mut attributes: Vec<(N, V)> = attributes.into_iter().collect();
+    attributes.sort_by_cached_key(|(name, _)| {
+        let mut string = String::new();
+        let _ = name.render(&mut string);
+        string
+    });
+    attributes
+}
+
+/// The error returned when an attribute name added with `..` is empty or contains whitespace, a
+/// quote, `<`, `>`, `&`, `/`, `=` or a control character, which could inject other attributes.
+#[derive(Debug, PartialEq)]
+pub struct InvalidAttributeName(pub String);
+
+impl std::fmt::Display for InvalidAttributeName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "attribute name {:?} is invalid", self.0)
+    }
+}
+
+impl std::error::Error for InvalidAttributeName {}
+
+// Renders an attribute name added with `..`. The name is buffered so that nothing of an invalid
+// one is written, even when the error is ignored.
+#[doc(hidden)]
+pub struct SpreadName<T>(pub T);
+
+impl<T: Render> Render for SpreadName<T> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        let mut name = String::new();
+        self.0.render(&mut name)?;
+        // `"`, `<`, `>` and `&` are escaped to entities by now, so checking `&` covers them.
+        let invalid = |char: char| {
+            char.is_whitespace()
+                || char.is_control()
+                || matches!(char, '"' | '\'' | '<' | '>' | '&' | '/' | '=')
+        };
+        if name.is_empty() || name.contains(invalid) {
+            return Err(RenderError::wrap(InvalidAttributeName(name)));
+        }
+        Ok(writer.write_str(&name)?)
+    }
+}
+
 macro_rules! tfor {
-    (for $ty:ident in [$($typ:ident),*] $tt:tt) => {
+    (for $ty:ident in [$($typ:ty),*] $tt:tt) => {
         $( const _: () = { type $ty = $typ; tfor! { @extract $tt } }; )*
     };
     (@extract { $($tt:tt)* }) => { $($tt)* };
@@ -136,8 +517,8 @@ tfor! {
     for Ty in [char, f32, f64] {
         impl Render for Ty {
             #[inline]
-            fn render(&self, writer: &mut impl std::fmt::Write) -> std::fmt::Result {
-                write!(writer, "{}", self)
+            fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+                Ok(write!(writer, "{}", self)?)
             }
         }
 
@@ -150,14 +531,14 @@ tfor! {
     for Ty in [u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize] {
         impl Render for Ty {
             #[inline]
-            fn render(&self, writer: &mut impl std::fmt::Write) -> std::fmt::Result {
+            fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
                 #[cfg(feature = "itoa")] {
                     let mut buffer = itoa::Buffer::new();
                     let str = buffer.format(*self);
-                    writer.write_str(str)
+                    Ok(writer.write_str(str)?)
                 }
                 #[cfg(not(feature = "itoa"))] {
-                    write!(writer, "{}", self)
+                    Ok(write!(writer, "{}", self)?)
                 }
             }
         }
@@ -167,10 +548,47 @@ tfor! {
     }
 }
 
+tfor! {
+    for Ty in [
+        std::num::NonZeroU8, std::num::NonZeroU16, std::num::NonZeroU32, std::num::NonZeroU64,
+        std::num::NonZeroU128, std::num::NonZeroUsize, std::num::NonZeroI8, std::num::NonZeroI16,
+        std::num::NonZeroI32, std::num::NonZeroI64, std::num::NonZeroI128, std::num::NonZeroIsize
+    ] {
+        impl Render for Ty {
+            #[inline]
+            fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+                self.get().render(writer)
+            }
+        }
+
+        impl RenderAttributeValue for Ty {
+        }
+    }
+}
+
+// Addresses are made of digits, hex digits, `.`, `:`, `[`, `]` and `%`, none of which need
+// escaping.
+tfor! {
+    for Ty in [
+        std::net::IpAddr, std::net::Ipv4Addr, std::net::Ipv6Addr, std::net::SocketAddr,
+        std::net::SocketAddrV4, std::net::SocketAddrV6
+    ] {
+        impl Render for Ty {
+            #[inline]
+            fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+                Ok(write!(writer, "{}", self)?)
+            }
+        }
+
+        impl RenderAttributeValue for Ty {
+        }
+    }
+}
+
 impl Render for str {
     #[inline]
-    fn render(&self, writer: &mut impl std::fmt::Write) -> std::fmt::Result {
-        escape::escape(self, writer)
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        Ok(escape::escape(self, writer)?)
     }
 }
 
@@ -178,7 +596,7 @@ impl RenderAttributeValue for str {}
 
 impl Render for String {
     #[inline]
-    fn render(&self, writer: &mut impl std::fmt::Write) -> std::fmt::Result {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
         self.as_str().render(writer)
     }
 }
@@ -187,19 +605,77 @@ impl RenderAttributeValue for String {}
 
 impl Render for std::fmt::Arguments<'_> {
     #[inline]
-    fn render(&self, writer: &mut impl std::fmt::Write) -> std::fmt::Result {
-        escape::Escape(writer).write_fmt(*self)
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        Ok(escape::Escape(writer).write_fmt(*self)?)
     }
 }
 
 impl RenderAttributeValue for std::fmt::Arguments<'_> {}
 
+#[cfg(feature = "camino")]
+impl Render for camino::Utf8Path {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        self.as_str().render(writer)
+    }
+}
+
+#[cfg(feature = "camino")]
+impl RenderAttributeValue for camino::Utf8Path {}
+
+#[cfg(feature = "camino")]
+impl Render for camino::Utf8PathBuf {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        self.as_str().render(writer)
+    }
+}
+
+#[cfg(feature = "camino")]
+impl RenderAttributeValue for camino::Utf8PathBuf {}
+
+// Parameter values can be quoted strings containing `"`, so the output is escaped like any string.
+#[cfg(feature = "mime")]
+impl Render for mime::Mime {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        self.as_ref().render(writer)
+    }
+}
+
+#[cfg(feature = "mime")]
+impl RenderAttributeValue for mime::Mime {}
+
+// Versions only contain alphanumerics, `.`, `-` and `+`.
+#[cfg(feature = "semver")]
+impl Render for semver::Version {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        Ok(write!(writer, "{}", self)?)
+    }
+}
+
+#[cfg(feature = "semver")]
+impl RenderAttributeValue for semver::Version {}
+
+// Requirements contain `<` and `>` comparators.
+#[cfg(feature = "semver")]
+impl Render for semver::VersionReq {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        Ok(write!(escape::Escape(writer), "{}", self)?)
+    }
+}
+
+#[cfg(feature = "semver")]
+impl RenderAttributeValue for semver::VersionReq {}
+
 macro_rules! tuple_impl {
     ($($ident:ident)+) => {
         impl<$($ident: Render,)+> Render for ($($ident,)+) {
             #[allow(non_snake_case)]
             #[inline]
-            fn render(&self, writer: &mut impl std::fmt::Write) -> std::fmt::Result {
+            fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
                 let ($(ref $ident,)+) = *self;
                 $($ident.render(writer)?;)+
                 Ok(())
@@ -222,20 +698,31 @@ tuple_impl! { A B C D E F G H }
 tuple_impl! { A B C D E F G H I }
 tuple_impl! { A B C D E F G H I J }
 
+type RenderFn<'a> = dyn Fn(&mut dyn std::fmt::Write) -> Result<(), RenderError> + 'a;
+
 pub struct DynRender<'a> {
-    f: Box<dyn Fn(&mut dyn std::fmt::Write) -> std::fmt::Result + 'a>,
+    f: Box<RenderFn<'a>>,
 }
 
 pub fn new<'a, F>(f: F) -> DynRender<'a>
 where
-    F: Fn(&mut dyn std::fmt::Write) -> std::fmt::Result + 'a,
+    F: Fn(&mut dyn std::fmt::Write) -> Result<(), RenderError> + 'a,
 {
     DynRender { f: Box::new(f) }
 }
 
+/// Like [`new`], for closures returning any error. Errors are converted with
+/// [`RenderError::wrap`], so `?` works on any error type inside the closure.
+pub fn try_new<'a, F>(f: F) -> DynRender<'a>
+where
+    F: Fn(&mut dyn std::fmt::Write) -> Result<(), Box<dyn std::error::Error + Send + Sync>> + 'a,
+{
+    new(move |writer| f(writer).map_err(RenderError::from))
+}
+
 impl<'a> Render for DynRender<'a> {
     #[inline]
-    fn render(&self, writer: &mut impl std::fmt::Write) -> std::fmt::Result {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
         (self.f)(writer)
     }
 }
@@ -243,7 +730,176 @@ impl<'a> Render for DynRender<'a> {
 impl<'a> std::fmt::Display for DynRender<'a> {
     #[inline]
     fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
-        Render::render(self, fmt)
+        Render::render(self, fmt).map_err(|_| std::fmt::Error)
+    }
+}
+
+/// Implements [`std::fmt::Display`] for any [`Render`] value, see [`display`].
+pub struct RenderDisplay<T> {
+    template: T,
+    error: std::cell::Cell<Option<RenderError>>,
+}
+
+/// Wraps `template` so it can be used wherever `Display` is expected, e.g. with `format!` or as a
+/// `tracing` field. Formatting options like a width are ignored. `Display` can only fail with
+/// [`std::fmt::Error`], so if rendering fails the error is kept and can be retrieved with
+/// [`RenderDisplay::take_error`]. Note that `format!` and `to_string` panic when a `Display` impl
+/// fails; `write!` returns the error.
+pub fn display<T: Render>(template: T) -> RenderDisplay<T> {
+    RenderDisplay {
+        template,
+        error: std::cell::Cell::new(None),
+    }
+}
+
+impl<T> RenderDisplay<T> {
+    /// The error of the last failed render, if any.
+    pub fn take_error(&self) -> Option<RenderError> {
+        self.error.take()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.template
+    }
+}
+
+impl<T: Render> std::fmt::Display for RenderDisplay<T> {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.template.render(fmt).map_err(|error| {
+            self.error.set(Some(error));
+            std::fmt::Error
+        })
+    }
+}
+
+/// An object safe version of [`Render`], implemented for every sized `Render` type, so different
+/// templates can be stored as `Box<dyn RenderDyn>`.
+pub trait RenderDyn {
+    fn render_dyn(&self, writer: &mut dyn std::fmt::Write) -> Result<(), RenderError>;
+}
+
+impl<T: Render> RenderDyn for T {
+    #[inline]
+    fn render_dyn(&self, mut writer: &mut dyn std::fmt::Write) -> Result<(), RenderError> {
+        self.render(&mut writer)
+    }
+}
+
+impl Render for dyn RenderDyn + '_ {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        self.render_dyn(writer)
+    }
+}
+
+impl Render for dyn RenderDyn + Send + Sync + '_ {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        self.render_dyn(writer)
+    }
+}
+
+pub struct TryIter<I>(std::cell::Cell<Option<I>>);
+
+impl<I, T, E> Render for TryIter<I>
+where
+    I: Iterator<Item = Result<T, E>>,
+    T: Render,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        if let Some(iter) = self.0.take() {
+            for item in iter {
+                item.map_err(RenderError::wrap)?.render(writer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renders the `Ok` items of `iter` in order, failing with the first `Err`. Items before the error
+/// have already been written by then. The iterator is consumed by the first render, rendering
+/// again writes nothing. Use `@for item? in iter { ... }` to do the same in a template.
+pub fn try_iter<I, T, E>(iter: I) -> TryIter<I::IntoIter>
+where
+    I: IntoIterator<Item = Result<T, E>>,
+{
+    TryIter(std::cell::Cell::new(Some(iter.into_iter())))
+}
+
+pub struct FromIter<I>(I);
+
+impl<I> Render for FromIter<I>
+where
+    I: Iterator + Clone,
+    I::Item: Render,
+{
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        for item in self.0.clone() {
+            item.render(writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders the items of `iter` in order, stopping at the first error. Every render iterates a
+/// clone of the iterator, so the result can be rendered more than once, e.g. once to measure it
+/// and once to write it. Iterators over slices and their `map`s are cheap to clone.
+pub fn from_iter<I>(iter: I) -> FromIter<I::IntoIter>
+where
+    I: IntoIterator,
+    I::IntoIter: Clone,
+    I::Item: Render,
+{
+    FromIter(iter.into_iter())
+}
+
+pub struct Lazy<F, R> {
+    f: std::cell::Cell<Option<F>>,
+    value: std::cell::RefCell<Option<R>>,
+}
+
+impl<F: FnOnce() -> R, R> Lazy<F, R> {
+    fn with<T>(&self, f: impl FnOnce(&R) -> T) -> T {
+        if self.value.borrow().is_none() {
+            let value = (self.f.take().expect("`Lazy` closure already taken"))();
+            *self.value.borrow_mut() = Some(value);
+        }
+        f(self.value.borrow().as_ref().expect("initialized above"))
+    }
+}
+
+impl<F: FnOnce() -> R, R: Render> Render for Lazy<F, R> {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        self.with(|value| value.render(writer))
+    }
+}
+
+impl<F: FnOnce() -> R, R: RenderAttributeValue> RenderAttributeValue for Lazy<F, R> {
+    #[inline]
+    fn is_none(&self) -> bool {
+        self.with(R::is_none)
+    }
+
+    #[inline]
+    fn is_true(&self) -> bool {
+        self.with(R::is_true)
+    }
+
+    #[inline]
+    fn is_false(&self) -> bool {
+        self.with(R::is_false)
+    }
+}
+
+/// Renders the value returned by `f`, which is only called when the value is first rendered, e.g.
+/// for an expensive value in a branch which might not be taken. The value is kept, so rendering
+/// again doesn't call `f` again.
+pub fn lazy<F: FnOnce() -> R, R: Render>(f: F) -> Lazy<F, R> {
+    Lazy {
+        f: std::cell::Cell::new(Some(f)),
+        value: std::cell::RefCell::new(None),
     }
 }
 
@@ -251,3 +907,69 @@ impl<'a> std::fmt::Display for DynRender<'a> {
 pub fn doctype() -> impl Render {
     raw("<!DOCTYPE html>")
 }
+
+struct XmlDeclaration<'a> {
+    version: &'a str,
+    encoding: &'a str,
+}
+
+impl Render for XmlDeclaration<'_> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        writer.write_str("<?xml version=\"")?;
+        escape_attribute(self.version, &mut *writer)?;
+        writer.write_str("\" encoding=\"")?;
+        escape_attribute(self.encoding, &mut *writer)?;
+        writer.write_str("\"?>")?;
+        Ok(())
+    }
+}
+
+/// Writes `<?xml version="..." encoding="..."?>`, the XML counterpart of [`doctype`].
+#[inline]
+pub fn xml_declaration<'a>(version: &'a str, encoding: &'a str) -> impl Render + 'a {
+    XmlDeclaration { version, encoding }
+}
+
+#[cfg(feature = "serde_json")]
+struct Json<'a, T: ?Sized>(&'a T);
+
+#[cfg(feature = "serde_json")]
+impl<T: serde::Serialize + ?Sized> Render for Json<'_, T> {
+    #[inline]
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        json::write(writer, self.0)
+    }
+}
+
+/// Renders `value` as JSON for embedding into a `<script>` element, e.g.
+/// `script[type = "application/json", id = "data"] { @markup::json(&data) }`. Instead of HTML
+/// escaping, `<`, `>`, `&`, U+2028 and U+2029 are written as `\u003c`, `\u003e`, `\u0026`,
+/// `\u2028` and `\u2029`, so strings can't end the element and the output parses as the same
+/// JSON. Serialization errors are returned as `RenderError::Other`.
+#[cfg(feature = "serde_json")]
+#[inline]
+pub fn json<T: serde::Serialize + ?Sized>(value: &T) -> impl Render + '_ {
+    Json(value)
+}
+
+#[cfg(feature = "serde_json")]
+struct JsonLd<'a, T: ?Sized>(&'a T);
+
+#[cfg(feature = "serde_json")]
+impl<T: serde::Serialize + ?Sized> Render for JsonLd<'_, T> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        writer.write_str(r#"<script type="application/ld+json">"#)?;
+        json::write(writer, self.0)?;
+        writer.write_str("</script>")?;
+        Ok(())
+    }
+}
+
+/// Renders `value` as JSON inside `<script type="application/ld+json">`. Strings can't close the
+/// script element since `<`, `>` and `&` are written as `\u003c`, `\u003e` and `\u0026`.
+/// Serialization errors are returned as `RenderError::Other`.
+#[cfg(feature = "serde_json")]
+#[inline]
+pub fn json_ld<T: serde::Serialize + ?Sized>(value: &T) -> impl Render + '_ {
+    JsonLd(value)
+}
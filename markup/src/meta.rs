@@ -0,0 +1,137 @@
+//! Builders for Open Graph and Twitter card `<meta>` tags.
+
+use crate::{Render, RenderError};
+use std::borrow::Cow;
+
+type Properties<'a> = Vec<(Cow<'a, str>, Cow<'a, str>)>;
+
+fn set<'a>(properties: &mut Properties<'a>, name: Cow<'a, str>, content: Cow<'a, str>) {
+    match properties
+        .iter_mut()
+        .find(|(existing, _)| *existing == name)
+    {
+        Some((_, existing)) => *existing = content,
+        None => properties.push((name, content)),
+    }
+}
+
+fn render(
+    properties: &Properties,
+    attribute: &str,
+    writer: &mut impl std::fmt::Write,
+) -> Result<(), RenderError> {
+    for (name, content) in properties {
+        write!(writer, "<meta {}=\"", attribute)?;
+        name.render(writer)?;
+        writer.write_str("\" content=\"")?;
+        content.render(writer)?;
+        writer.write_str("\">")?;
+    }
+    Ok(())
+}
+
+/// `<meta property="og:..." content="...">` tags, rendered in the order they were first set.
+#[derive(Clone, Debug, Default)]
+pub struct OpenGraph<'a> {
+    properties: Properties<'a>,
+}
+
+pub fn og<'a>() -> OpenGraph<'a> {
+    OpenGraph::default()
+}
+
+macro_rules! properties {
+    ($($method:ident => $name:literal,)*) => {
+        $(
+            pub fn $method(self, content: impl Into<Cow<'a, str>>) -> Self {
+                self.custom($name, content)
+            }
+        )*
+    };
+}
+
+impl<'a> OpenGraph<'a> {
+    properties! {
+        title => "og:title",
+        description => "og:description",
+        image => "og:image",
+        url => "og:url",
+        site_name => "og:site_name",
+        kind => "og:type",
+        locale => "og:locale",
+    }
+
+    pub fn custom(
+        mut self,
+        property: impl Into<Cow<'a, str>>,
+        content: impl Into<Cow<'a, str>>,
+    ) -> Self {
+        set(&mut self.properties, property.into(), content.into());
+        self
+    }
+}
+
+impl Render for OpenGraph<'_> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        render(&self.properties, "property", writer)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Card {
+    Summary,
+    SummaryLargeImage,
+    App,
+    Player,
+}
+
+impl Card {
+    fn as_str(self) -> &'static str {
+        match self {
+            Card::Summary => "summary",
+            Card::SummaryLargeImage => "summary_large_image",
+            Card::App => "app",
+            Card::Player => "player",
+        }
+    }
+}
+
+/// `<meta name="twitter:..." content="...">` tags, rendered in the order they were first set.
+#[derive(Clone, Debug, Default)]
+pub struct TwitterCard<'a> {
+    properties: Properties<'a>,
+}
+
+pub fn twitter<'a>() -> TwitterCard<'a> {
+    TwitterCard::default()
+}
+
+impl<'a> TwitterCard<'a> {
+    properties! {
+        site => "twitter:site",
+        creator => "twitter:creator",
+        title => "twitter:title",
+        description => "twitter:description",
+        image => "twitter:image",
+        image_alt => "twitter:image:alt",
+    }
+
+    pub fn card(self, card: Card) -> Self {
+        self.custom("twitter:card", card.as_str())
+    }
+
+    pub fn custom(
+        mut self,
+        name: impl Into<Cow<'a, str>>,
+        content: impl Into<Cow<'a, str>>,
+    ) -> Self {
+        set(&mut self.properties, name.into(), content.into());
+        self
+    }
+}
+
+impl Render for TwitterCard<'_> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        render(&self.properties, "name", writer)
+    }
+}
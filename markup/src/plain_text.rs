@@ -0,0 +1,268 @@
+use crate::{Render, RenderError};
+
+// Elements ending a paragraph (a blank line) or a line.
+const PARAGRAPHS: &[&str] = &[
+    "p",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "table",
+    "blockquote",
+];
+const LINES: &[&str] = &[
+    "div", "tr", "ul", "ol", "li", "pre", "hr", "header", "footer",
+];
+
+enum State {
+    Text,
+    Entity(String),
+    Tag(String, Option<char>),
+}
+
+/// A writer converting rendered HTML into plain text, e.g. for the `text/plain` alternative of an
+/// email. It is a best effort conversion of the markup this crate generates:
+///
+/// * Tags are removed, as are comments, `<!DOCTYPE>` and the contents of `<head>`, `<script>` and
+///   `<style>`.
+/// * Entities are decoded.
+/// * Runs of whitespace become a single space, and whitespace at the start and the end of lines
+///   is removed.
+/// * `<br>` starts a new line, and so does the end of `<div>`, `<tr>`, `<ul>`, `<ol>` and `<li>`.
+///   The end of `<p>`, `<h1>` to `<h6>`, `<table>` and `<blockquote>` leaves an empty line.
+/// * Every `<li>` starts with `- `.
+/// * `<a href="url">text</a>` becomes `text (url)`, unless the text is the url itself.
+/// * Table cells are separated by a space.
+///
+/// Input can be split anywhere across `write_str` calls. Call [`PlainTextWriter::finish`] after
+/// the last write to get back the inner writer.
+pub struct PlainTextWriter<W> {
+    inner: W,
+    state: State,
+    // The element whose contents are skipped.
+    skip: Option<String>,
+    started: bool,
+    line_start: bool,
+    space: bool,
+    newlines: usize,
+    links: Vec<(Option<String>, String)>,
+}
+
+impl<W: std::fmt::Write> PlainTextWriter<W> {
+    pub fn new(inner: W) -> Self {
+        PlainTextWriter {
+            inner,
+            state: State::Text,
+            skip: None,
+            started: false,
+            line_start: true,
+            space: false,
+            newlines: 0,
+            links: Vec::new(),
+        }
+    }
+
+    /// Writes out an unterminated entity, if any, and returns the inner writer.
+    pub fn finish(mut self) -> Result<W, std::fmt::Error> {
+        if let State::Entity(entity) = std::mem::replace(&mut self.state, State::Text) {
+            self.text_str(&entity)?;
+        }
+        Ok(self.inner)
+    }
+
+    fn text(&mut self, char: char) -> std::fmt::Result {
+        if self.skip.is_some() {
+            return Ok(());
+        }
+        if let Some((_, text)) = self.links.last_mut() {
+            text.push(char);
+        }
+        if char.is_whitespace() && char != '\u{a0}' {
+            self.space = !self.line_start;
+            return Ok(());
+        }
+        self.flush()?;
+        self.inner.write_char(char)?;
+        self.line_start = false;
+        Ok(())
+    }
+
+    fn text_str(&mut self, str: &str) -> std::fmt::Result {
+        str.chars().try_for_each(|char| self.text(char))
+    }
+
+    fn flush(&mut self) -> std::fmt::Result {
+        if self.newlines > 0 {
+            for _ in 0..self.newlines {
+                self.inner.write_char('\n')?;
+            }
+            self.newlines = 0;
+        } else if self.space {
+            self.inner.write_char(' ')?;
+        }
+        self.space = false;
+        self.started = true;
+        Ok(())
+    }
+
+    fn newlines(&mut self, count: usize) {
+        if self.started {
+            self.newlines = self.newlines.max(count);
+            self.space = false;
+            self.line_start = true;
+        }
+    }
+
+    fn tag(&mut self, tag: &str) -> std::fmt::Result {
+        if tag.starts_with('!') || tag.starts_with('?') {
+            return Ok(());
+        }
+        let (closing, tag) = match tag.strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, tag),
+        };
+        let end = tag
+            .find(|char: char| !char.is_ascii_alphanumeric())
+            .unwrap_or(tag.len());
+        let name = tag[..end].to_ascii_lowercase();
+
+        if let Some(skip) = &self.skip {
+            if closing && *skip == name {
+                self.skip = None;
+            }
+            return Ok(());
+        }
+
+        match (closing, &*name) {
+            (false, "head" | "script" | "style") => self.skip = Some(name),
+            (false, "br") if self.started => {
+                self.newlines += 1;
+                self.space = false;
+                self.line_start = true;
+            }
+            (false, "li") => {
+                self.newlines(1);
+                self.flush()?;
+                self.inner.write_str("- ")?;
+                self.line_start = true;
+            }
+            (false, "a") => self
+                .links
+                .push((attribute(&tag[end..], "href"), String::new())),
+            (true, "a") => {
+                if let Some((Some(href), text)) = self.links.pop() {
+                    if !href.is_empty() && text.trim() != href {
+                        self.text(' ')?;
+                        self.text_str(&format!("({})", href))?;
+                    }
+                }
+            }
+            (true, "td" | "th") => self.text(' ')?,
+            (true, name) if PARAGRAPHS.contains(&name) => self.newlines(2),
+            (true, name) if LINES.contains(&name) => self.newlines(1),
+            (false, "hr" | "p" | "div" | "table" | "tr" | "ul" | "ol") => self.newlines(1),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl<W: std::fmt::Write> std::fmt::Write for PlainTextWriter<W> {
+    fn write_str(&mut self, str: &str) -> std::fmt::Result {
+        for char in str.chars() {
+            match &mut self.state {
+                State::Text => match char {
+                    '<' => self.state = State::Tag(String::new(), None),
+                    '&' => self.state = State::Entity(String::from("&")),
+                    _ => self.text(char)?,
+                },
+                State::Entity(entity) => {
+                    if char == ';' {
+                        let decoded = crate::escape::decode_reference(&entity[1..]);
+                        let entity = std::mem::take(entity);
+                        self.state = State::Text;
+                        match decoded {
+                            Some(char) => self.text(char)?,
+                            None => {
+                                self.text_str(&entity)?;
+                                self.text(';')?;
+                            }
+                        }
+                    } else if (char.is_ascii_alphanumeric() || char == '#') && entity.len() < 10 {
+                        entity.push(char);
+                    } else {
+                        let entity = std::mem::take(entity);
+                        self.state = State::Text;
+                        self.text_str(&entity)?;
+                        std::fmt::Write::write_char(self, char)?;
+                    }
+                }
+                State::Tag(tag, quote) => match char {
+                    '"' | '\'' if *quote == Some(char) => {
+                        *quote = None;
+                        tag.push(char);
+                    }
+                    '"' | '\'' if quote.is_none() => {
+                        *quote = Some(char);
+                        tag.push(char);
+                    }
+                    // A `>` inside a comment doesn't end it.
+                    '>' if quote.is_none() && (!tag.starts_with("!--") || tag.ends_with("--")) => {
+                        let tag = std::mem::take(tag);
+                        self.state = State::Text;
+                        self.tag(&tag)?;
+                    }
+                    _ => tag.push(char),
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+// Finds the value of an attribute in the part of a start tag after its name.
+fn attribute(mut attributes: &str, name: &str) -> Option<String> {
+    loop {
+        attributes =
+            attributes.trim_start_matches(|char: char| char.is_whitespace() || char == '/');
+        if attributes.is_empty() {
+            return None;
+        }
+        let end = attributes
+            .find(|char: char| char.is_whitespace() || char == '=' || char == '/')
+            .unwrap_or(attributes.len());
+        let current = &attributes[..end];
+        attributes = attributes[end..].trim_start();
+        let value = match attributes.strip_prefix('=') {
+            Some(rest) => {
+                let rest = rest.trim_start();
+                let (value, rest) = match rest.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let rest = &rest[1..];
+                        let end = rest.find(quote).unwrap_or(rest.len());
+                        (&rest[..end], rest.get(end + 1..).unwrap_or(""))
+                    }
+                    _ => {
+                        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                        (&rest[..end], &rest[end..])
+                    }
+                };
+                attributes = rest;
+                value
+            }
+            None => "",
+        };
+        if current.eq_ignore_ascii_case(name) {
+            return Some(crate::escape::unescape(value).into_owned());
+        }
+    }
+}
+
+/// Renders `template` and converts the output to plain text with a [`PlainTextWriter`].
+pub fn to_plain_text(template: impl Render) -> Result<String, RenderError> {
+    let mut writer = PlainTextWriter::new(String::new());
+    template.render(&mut writer)?;
+    Ok(writer.finish()?)
+}
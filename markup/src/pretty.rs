@@ -0,0 +1,198 @@
+use crate::{Render, RenderError};
+
+// Elements without a closing tag.
+const VOID: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+// Elements whose contents are written as they are.
+const VERBATIM: &[&str] = &["pre", "script", "style", "textarea"];
+
+enum State {
+    Text(String),
+    Tag(String, Option<char>),
+    // The contents of a verbatim element, up to and including its closing tag.
+    Verbatim(String, String),
+}
+
+#[derive(PartialEq)]
+enum Last {
+    Nothing,
+    Open,
+    Other,
+}
+
+/// A writer indenting rendered HTML, one element or run of text per line, to make the output
+/// readable, e.g. in the expected output of tests. See [`pretty`].
+pub struct PrettyWriter<W> {
+    inner: W,
+    indent: usize,
+    state: State,
+    depth: usize,
+    last: Last,
+    // Text following a start tag, written on the same line if the element ends after it.
+    pending: Option<String>,
+}
+
+impl<W: std::fmt::Write> PrettyWriter<W> {
+    pub fn new(inner: W, indent: usize) -> Self {
+        PrettyWriter {
+            inner,
+            indent,
+            state: State::Text(String::new()),
+            depth: 0,
+            last: Last::Nothing,
+            pending: None,
+        }
+    }
+
+    /// Writes out any text after the last tag and returns the inner writer.
+    pub fn finish(mut self) -> Result<W, std::fmt::Error> {
+        match std::mem::replace(&mut self.state, State::Text(String::new())) {
+            State::Text(text) => self.text(&text)?,
+            State::Tag(tag, _) => {
+                self.flush()?;
+                self.line(&format!("<{}", tag))?;
+            }
+            State::Verbatim(_, contents) => self.inner.write_str(&contents)?,
+        }
+        self.flush()?;
+        Ok(self.inner)
+    }
+
+    fn line(&mut self, str: &str) -> std::fmt::Result {
+        if self.last != Last::Nothing {
+            self.inner.write_char('\n')?;
+        }
+        for _ in 0..self.depth * self.indent {
+            self.inner.write_char(' ')?;
+        }
+        self.inner.write_str(str)
+    }
+
+    fn flush(&mut self) -> std::fmt::Result {
+        if let Some(text) = self.pending.take() {
+            self.line(&text)?;
+            self.last = Last::Other;
+        }
+        Ok(())
+    }
+
+    fn text(&mut self, text: &str) -> std::fmt::Result {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(());
+        }
+        if self.last == Last::Open && self.pending.is_none() {
+            self.pending = Some(text.to_string());
+            return Ok(());
+        }
+        self.flush()?;
+        self.line(text)?;
+        self.last = Last::Other;
+        Ok(())
+    }
+
+    fn tag(&mut self, tag: &str) -> std::fmt::Result {
+        let name = tag.trim_start_matches('/');
+        let end = name
+            .find(|char: char| !char.is_ascii_alphanumeric() && char != '-')
+            .unwrap_or(name.len());
+        let name = name[..end].to_ascii_lowercase();
+
+        if tag.starts_with('/') {
+            self.depth = self.depth.saturating_sub(1);
+            if self.last == Last::Open {
+                if let Some(text) = self.pending.take() {
+                    self.inner.write_str(&text)?;
+                }
+                write!(self.inner, "<{}>", tag)?;
+            } else {
+                self.flush()?;
+                self.line(&format!("<{}>", tag))?;
+            }
+            self.last = Last::Other;
+            return Ok(());
+        }
+
+        self.flush()?;
+        self.line(&format!("<{}>", tag))?;
+        let empty = tag.starts_with('!') || tag.starts_with('?') || tag.ends_with('/');
+        if empty || VOID.contains(&&*name) {
+            self.last = Last::Other;
+        } else if VERBATIM.contains(&&*name) {
+            self.state = State::Verbatim(format!("</{}>", name), String::new());
+            self.last = Last::Other;
+        } else {
+            self.depth += 1;
+            self.last = Last::Open;
+        }
+        Ok(())
+    }
+}
+
+impl<W: std::fmt::Write> std::fmt::Write for PrettyWriter<W> {
+    fn write_str(&mut self, str: &str) -> std::fmt::Result {
+        for char in str.chars() {
+            match &mut self.state {
+                State::Text(text) => {
+                    if char == '<' {
+                        let text = std::mem::take(text);
+                        self.state = State::Tag(String::new(), None);
+                        self.text(&text)?;
+                    } else {
+                        text.push(char);
+                    }
+                }
+                State::Tag(tag, quote) => match char {
+                    '"' | '\'' if *quote == Some(char) => {
+                        *quote = None;
+                        tag.push(char);
+                    }
+                    '"' | '\'' if quote.is_none() && !tag.starts_with('!') => {
+                        *quote = Some(char);
+                        tag.push(char);
+                    }
+                    // A `>` inside a comment doesn't end it.
+                    '>' if quote.is_none() && (!tag.starts_with("!--") || tag.ends_with("--")) => {
+                        let tag = std::mem::take(tag);
+                        self.state = State::Text(String::new());
+                        self.tag(&tag)?;
+                    }
+                    _ => tag.push(char),
+                },
+                State::Verbatim(end, contents) => {
+                    contents.push(char);
+                    if contents.ends_with(&**end) {
+                        self.inner.write_str(contents)?;
+                        self.state = State::Text(String::new());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct Pretty<T> {
+    template: T,
+    indent: usize,
+}
+
+impl<T: Render> Render for Pretty<T> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        let mut writer = PrettyWriter::new(writer, self.indent);
+        self.template.render(&mut writer)?;
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+/// Renders `template` with every element and run of text on its own line, indented by `indent`
+/// spaces per level, for reading the output during development. Whitespace around text is
+/// replaced, so the result is only meant for humans and for comparing output. Elements containing
+/// only text are kept on one line, and the contents of `pre`, `script`, `style` and `textarea`
+/// are written as they are.
+pub fn pretty<T: Render>(template: T, indent: usize) -> Pretty<T> {
+    Pretty { template, indent }
+}
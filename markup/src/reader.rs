@@ -0,0 +1,136 @@
+use crate::{Render, RenderError};
+use std::io;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+
+const CHUNK_SIZE: usize = 8 * 1024;
+const CHANNEL_CHUNKS: usize = 2;
+
+enum Message {
+    Chunk(Vec<u8>),
+    Error(RenderError),
+    Done,
+}
+
+enum State<T> {
+    Pending(T),
+    Running(Receiver<Message>),
+    Done,
+}
+
+/// An [`io::Read`] streaming the output of a template.
+///
+/// The template is rendered on a separate thread, started by the first read, which hands over
+/// chunks of about 8 KiB (more if a single write is larger) through a bounded channel. At most a
+/// few chunks are buffered at a time, so rendering pauses while the reader falls behind. Output
+/// written before a render error is still read, after which the error is returned from `read` as
+/// an [`io::Error`] wrapping the [`RenderError`], whose source is the original error. Dropping the
+/// reader stops the render at its next write.
+pub struct RenderReader<T> {
+    state: State<T>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl<T: Render + Send + 'static> RenderReader<T> {
+    pub fn new(template: T) -> Self {
+        RenderReader {
+            state: State::Pending(template),
+            chunk: Vec::new(),
+            position: 0,
+        }
+    }
+
+    fn next_chunk(&mut self) -> io::Result<()> {
+        if let State::Pending(_) = self.state {
+            let template = match std::mem::replace(&mut self.state, State::Done) {
+                State::Pending(template) => template,
+                _ => unreachable!(),
+            };
+            let (sender, receiver) = sync_channel(CHANNEL_CHUNKS);
+            std::thread::spawn(move || render(template, sender));
+            self.state = State::Running(receiver);
+        }
+        let message = match &self.state {
+            State::Running(receiver) => receiver.recv().ok(),
+            _ => return Ok(()),
+        };
+        match message {
+            Some(Message::Chunk(chunk)) => {
+                self.chunk = chunk;
+                self.position = 0;
+                Ok(())
+            }
+            Some(Message::Done) => {
+                self.state = State::Done;
+                Ok(())
+            }
+            Some(Message::Error(error)) => {
+                self.state = State::Done;
+                Err(io::Error::new(io::ErrorKind::Other, error))
+            }
+            None => {
+                self.state = State::Done;
+                Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "render thread panicked",
+                ))
+            }
+        }
+    }
+}
+
+impl<T: Render + Send + 'static> io::Read for RenderReader<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.chunk.len() {
+            if let State::Done = self.state {
+                return Ok(0);
+            }
+            self.next_chunk()?;
+        }
+        let len = buf.len().min(self.chunk.len() - self.position);
+        buf[..len].copy_from_slice(&self.chunk[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+struct ChunkWriter {
+    sender: SyncSender<Message>,
+    chunk: String,
+}
+
+impl ChunkWriter {
+    fn send(&mut self) -> std::fmt::Result {
+        let chunk = std::mem::replace(&mut self.chunk, String::with_capacity(CHUNK_SIZE));
+        self.sender
+            .send(Message::Chunk(chunk.into_bytes()))
+            .map_err(|_| std::fmt::Error)
+    }
+}
+
+impl std::fmt::Write for ChunkWriter {
+    fn write_str(&mut self, str: &str) -> std::fmt::Result {
+        self.chunk.push_str(str);
+        if self.chunk.len() >= CHUNK_SIZE {
+            self.send()?;
+        }
+        Ok(())
+    }
+}
+
+fn render(template: impl Render, sender: SyncSender<Message>) {
+    let mut writer = ChunkWriter {
+        sender,
+        chunk: String::with_capacity(CHUNK_SIZE),
+    };
+    let result = template.render(&mut writer);
+    // A failed send means the reader is gone.
+    if !writer.chunk.is_empty() && writer.send().is_err() {
+        return;
+    }
+    let message = match result {
+        Ok(()) => Message::Done,
+        Err(error) => Message::Error(error),
+    };
+    let _ = writer.sender.send(message);
+}
@@ -0,0 +1,72 @@
+//! Name based dispatch to templates, for when the template to use is only known at runtime.
+
+use crate::{RenderDyn, RenderError};
+use std::collections::HashMap;
+
+type Constructor<C> = dyn for<'c> Fn(&'c C) -> Box<dyn RenderDyn + 'c> + Send + Sync;
+
+/// Maps names to constructors building a template from a context of type `C`.
+pub struct Registry<C: ?Sized> {
+    templates: HashMap<String, Box<Constructor<C>>>,
+}
+
+impl<C: ?Sized> Registry<C> {
+    pub fn new() -> Self {
+        Registry {
+            templates: HashMap::new(),
+        }
+    }
+
+    /// Registers a constructor under `name`, replacing any previous one.
+    pub fn insert<F>(&mut self, name: impl Into<String>, constructor: F) -> &mut Self
+    where
+        F: for<'c> Fn(&'c C) -> Box<dyn RenderDyn + 'c> + Send + Sync + 'static,
+    {
+        self.templates.insert(name.into(), Box::new(constructor));
+        self
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.templates.contains_key(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.templates.keys().map(String::as_str)
+    }
+
+    pub fn get<'c>(&self, name: &str, context: &'c C) -> Option<Box<dyn RenderDyn + 'c>> {
+        self.templates
+            .get(name)
+            .map(|constructor| constructor(context))
+    }
+
+    /// Renders the template registered under `name`, or fails with an [`UnknownTemplate`] error.
+    pub fn render(
+        &self,
+        name: &str,
+        context: &C,
+        writer: &mut impl std::fmt::Write,
+    ) -> Result<(), RenderError> {
+        match self.get(name, context) {
+            Some(template) => template.render_dyn(writer),
+            None => Err(RenderError::wrap(UnknownTemplate(name.to_string()))),
+        }
+    }
+}
+
+impl<C: ?Sized> Default for Registry<C> {
+    fn default() -> Self {
+        Registry::new()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UnknownTemplate(pub String);
+
+impl std::fmt::Display for UnknownTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no template registered as {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownTemplate {}
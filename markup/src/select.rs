@@ -0,0 +1,158 @@
+use crate::{Render, RenderError};
+use std::borrow::Cow;
+
+type Options<'a> = Vec<(Cow<'a, str>, Cow<'a, str>)>;
+
+enum Item<'a> {
+    Option(Cow<'a, str>, Cow<'a, str>),
+    Group(Cow<'a, str>, Options<'a>),
+}
+
+pub struct Select<'a> {
+    name: Cow<'a, str>,
+    items: Vec<Item<'a>>,
+    selected: Option<Cow<'a, str>>,
+    placeholder: Option<Cow<'a, str>>,
+    attrs: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+}
+
+/// Builds a `<select name="...">` element. Options are `(value, label)` pairs, and the one whose
+/// value equals [`Select::selected`] is marked `selected`.
+pub fn select<'a>(name: impl Into<Cow<'a, str>>) -> Select<'a> {
+    Select {
+        name: name.into(),
+        items: Vec::new(),
+        selected: None,
+        placeholder: None,
+        attrs: Vec::new(),
+    }
+}
+
+fn options<'a, I, V, L>(options: I) -> impl Iterator<Item = (Cow<'a, str>, Cow<'a, str>)>
+where
+    I: IntoIterator<Item = (V, L)>,
+    V: Into<Cow<'a, str>>,
+    L: Into<Cow<'a, str>>,
+{
+    options
+        .into_iter()
+        .map(|(value, label)| (value.into(), label.into()))
+}
+
+impl<'a> Select<'a> {
+    pub fn options<I, V, L>(mut self, options: I) -> Self
+    where
+        I: IntoIterator<Item = (V, L)>,
+        V: Into<Cow<'a, str>>,
+        L: Into<Cow<'a, str>>,
+    {
+        self.items
+            .extend(self::options(options).map(|(value, label)| Item::Option(value, label)));
+        self
+    }
+
+    /// Adds the options inside an `<optgroup label="...">`.
+    pub fn optgroup<I, V, L>(mut self, label: impl Into<Cow<'a, str>>, options: I) -> Self
+    where
+        I: IntoIterator<Item = (V, L)>,
+        V: Into<Cow<'a, str>>,
+        L: Into<Cow<'a, str>>,
+    {
+        self.items
+            .push(Item::Group(label.into(), self::options(options).collect()));
+        self
+    }
+
+    pub fn selected<V: Into<Cow<'a, str>>>(mut self, selected: Option<V>) -> Self {
+        self.selected = selected.map(Into::into);
+        self
+    }
+
+    /// Adds a disabled first option with an empty value. It is selected when nothing else is.
+    pub fn placeholder(mut self, label: impl Into<Cow<'a, str>>) -> Self {
+        self.placeholder = Some(label.into());
+        self
+    }
+
+    /// Adds attributes to the `<select>` element, e.g. `("required", "")` or `("id", "country")`.
+    pub fn attrs<I, N, V>(mut self, attrs: I) -> Self
+    where
+        I: IntoIterator<Item = (N, V)>,
+        N: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        self.attrs.extend(
+            attrs
+                .into_iter()
+                .map(|(name, value)| (name.into(), value.into())),
+        );
+        self
+    }
+
+    fn option(
+        &self,
+        writer: &mut impl std::fmt::Write,
+        value: &str,
+        label: &str,
+    ) -> Result<(), RenderError> {
+        writer.write_str("<option value=\"")?;
+        value.render(writer)?;
+        writer.write_str("\"")?;
+        if self.is_selected(value) {
+            writer.write_str(" selected")?;
+        }
+        writer.write_str(">")?;
+        label.render(writer)?;
+        writer.write_str("</option>")?;
+        Ok(())
+    }
+
+    fn is_selected(&self, value: &str) -> bool {
+        self.selected.as_deref() == Some(value)
+    }
+}
+
+impl Render for Select<'_> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        writer.write_str("<select name=\"")?;
+        self.name.render(writer)?;
+        writer.write_str("\"")?;
+        for (name, value) in &self.attrs {
+            writer.write_str(" ")?;
+            name.render(writer)?;
+            writer.write_str("=\"")?;
+            value.render(writer)?;
+            writer.write_str("\"")?;
+        }
+        writer.write_str(">")?;
+        if let Some(placeholder) = &self.placeholder {
+            let selected = !self.items.iter().any(|item| match item {
+                Item::Option(value, _) => self.is_selected(value),
+                Item::Group(_, options) => options.iter().any(|(value, _)| self.is_selected(value)),
+            });
+            writer.write_str("<option value=\"\" disabled")?;
+            if selected {
+                writer.write_str(" selected")?;
+            }
+            writer.write_str(">")?;
+            placeholder.render(writer)?;
+            writer.write_str("</option>")?;
+        }
+        for item in &self.items {
+            match item {
+                Item::Option(value, label) => self.option(writer, value, label)?,
+                Item::Group(label, options) => {
+                    writer.write_str("<optgroup label=\"")?;
+                    label.render(writer)?;
+                    writer.write_str("\">")?;
+                    for (value, label) in options {
+                        self.option(writer, value, label)?;
+                    }
+                    writer.write_str("</optgroup>")?;
+                }
+            }
+        }
+        writer.write_str("</select>")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+//! Builders for `sitemap.xml` files and sitemap indexes.
+
+use crate::feed::{element, Timestamp};
+use crate::{Render, RenderError};
+use std::borrow::Cow;
+
+/// The maximum number of URLs in a sitemap, and of sitemaps in an index.
+pub const MAX_ENTRIES: usize = 50_000;
+
+/// Returned as the source of a [`RenderError`] when a sitemap or an index has more than
+/// [`MAX_ENTRIES`] entries.
+#[derive(Debug)]
+pub struct TooManyEntries(pub usize);
+
+impl std::fmt::Display for TooManyEntries {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "sitemap has {} entries, at most {} are allowed",
+            self.0, MAX_ENTRIES
+        )
+    }
+}
+
+impl std::error::Error for TooManyEntries {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Changefreq {
+    Always,
+    Hourly,
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+    Never,
+}
+
+impl Changefreq {
+    fn as_str(self) -> &'static str {
+        match self {
+            Changefreq::Always => "always",
+            Changefreq::Hourly => "hourly",
+            Changefreq::Daily => "daily",
+            Changefreq::Weekly => "weekly",
+            Changefreq::Monthly => "monthly",
+            Changefreq::Yearly => "yearly",
+            Changefreq::Never => "never",
+        }
+    }
+}
+
+pub fn url<'a>(loc: impl Into<Cow<'a, str>>) -> Url<'a> {
+    Url {
+        loc: loc.into(),
+        lastmod: None,
+        changefreq: None,
+        priority: None,
+    }
+}
+
+pub struct Url<'a> {
+    loc: Cow<'a, str>,
+    lastmod: Option<String>,
+    changefreq: Option<Changefreq>,
+    priority: Option<f32>,
+}
+
+impl Url<'_> {
+    /// Accepts a date (`2023-01-02`) or a full timestamp.
+    pub fn lastmod(mut self, lastmod: impl Timestamp) -> Self {
+        self.lastmod = Some(lastmod.rfc3339().into_owned());
+        self
+    }
+
+    pub fn changefreq(mut self, changefreq: Changefreq) -> Self {
+        self.changefreq = Some(changefreq);
+        self
+    }
+
+    /// The priority is clamped to `0.0..=1.0` and written with one decimal. NaN removes it.
+    pub fn priority(mut self, priority: f32) -> Self {
+        self.priority = if priority.is_nan() {
+            None
+        } else {
+            Some(priority.clamp(0.0, 1.0))
+        };
+        self
+    }
+}
+
+impl Render for Url<'_> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        writer.write_str("<url>")?;
+        element(writer, "loc", &self.loc)?;
+        if let Some(lastmod) = &self.lastmod {
+            element(writer, "lastmod", lastmod)?;
+        }
+        if let Some(changefreq) = self.changefreq {
+            element(writer, "changefreq", changefreq.as_str())?;
+        }
+        if let Some(priority) = self.priority {
+            write!(writer, "<priority>{:.1}</priority>", priority)?;
+        }
+        writer.write_str("</url>")?;
+        Ok(())
+    }
+}
+
+/// A `<urlset>` document. Rendering fails with [`TooManyEntries`] above [`MAX_ENTRIES`] URLs.
+#[derive(Default)]
+pub struct UrlSet<'a> {
+    urls: Vec<Url<'a>>,
+}
+
+impl<'a> UrlSet<'a> {
+    pub fn new() -> Self {
+        UrlSet { urls: Vec::new() }
+    }
+
+    pub fn url(mut self, url: Url<'a>) -> Self {
+        self.urls.push(url);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.urls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.urls.is_empty()
+    }
+}
+
+impl<'a> FromIterator<Url<'a>> for UrlSet<'a> {
+    fn from_iter<I: IntoIterator<Item = Url<'a>>>(iter: I) -> Self {
+        UrlSet {
+            urls: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<'a> Extend<Url<'a>> for UrlSet<'a> {
+    fn extend<I: IntoIterator<Item = Url<'a>>>(&mut self, iter: I) {
+        self.urls.extend(iter);
+    }
+}
+
+impl Render for UrlSet<'_> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        if self.urls.len() > MAX_ENTRIES {
+            return Err(RenderError::wrap(TooManyEntries(self.urls.len())));
+        }
+        writer.write_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writer.write_str(r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#)?;
+        for url in &self.urls {
+            url.render(writer)?;
+        }
+        writer.write_str("</urlset>")?;
+        Ok(())
+    }
+}
+
+pub fn sitemap<'a>(loc: impl Into<Cow<'a, str>>) -> Sitemap<'a> {
+    Sitemap {
+        loc: loc.into(),
+        lastmod: None,
+    }
+}
+
+/// An entry of a [`SitemapIndex`], pointing to one sitemap.
+pub struct Sitemap<'a> {
+    loc: Cow<'a, str>,
+    lastmod: Option<String>,
+}
+
+impl Sitemap<'_> {
+    pub fn lastmod(mut self, lastmod: impl Timestamp) -> Self {
+        self.lastmod = Some(lastmod.rfc3339().into_owned());
+        self
+    }
+}
+
+impl Render for Sitemap<'_> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        writer.write_str("<sitemap>")?;
+        element(writer, "loc", &self.loc)?;
+        if let Some(lastmod) = &self.lastmod {
+            element(writer, "lastmod", lastmod)?;
+        }
+        writer.write_str("</sitemap>")?;
+        Ok(())
+    }
+}
+
+/// A `<sitemapindex>` document, for sites split over several sitemaps.
+#[derive(Default)]
+pub struct SitemapIndex<'a> {
+    sitemaps: Vec<Sitemap<'a>>,
+}
+
+impl<'a> SitemapIndex<'a> {
+    pub fn new() -> Self {
+        SitemapIndex {
+            sitemaps: Vec::new(),
+        }
+    }
+
+    pub fn sitemap(mut self, sitemap: Sitemap<'a>) -> Self {
+        self.sitemaps.push(sitemap);
+        self
+    }
+}
+
+impl<'a> FromIterator<Sitemap<'a>> for SitemapIndex<'a> {
+    fn from_iter<I: IntoIterator<Item = Sitemap<'a>>>(iter: I) -> Self {
+        SitemapIndex {
+            sitemaps: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Render for SitemapIndex<'_> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        if self.sitemaps.len() > MAX_ENTRIES {
+            return Err(RenderError::wrap(TooManyEntries(self.sitemaps.len())));
+        }
+        writer.write_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writer
+            .write_str(r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#)?;
+        for sitemap in &self.sitemaps {
+            sitemap.render(writer)?;
+        }
+        writer.write_str("</sitemapindex>")?;
+        Ok(())
+    }
+}
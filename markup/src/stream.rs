@@ -0,0 +1,83 @@
+use crate::{Render, RenderError};
+use std::collections::VecDeque;
+
+enum State<T> {
+    Pending(T),
+    Rendered(Option<RenderError>),
+}
+
+/// An iterator over the output of a template in chunks, returned by [`stream`].
+///
+/// The template is rendered in full by the first call to `next`, on the calling thread, and the
+/// chunks are kept until they are taken, so memory use is bounded by the size of the output, not
+/// by the chunk size. Use [`RenderReader`](crate::RenderReader) to render on a separate thread with
+/// only a few chunks buffered at a time.
+///
+/// Every chunk except the last one is exactly `chunk_size` bytes long. A template rendering
+/// nothing yields no chunks. If rendering fails, the output written before the error is yielded
+/// first, then the error, after which the iterator ends.
+pub struct RenderStream<T> {
+    state: State<T>,
+    chunk_size: usize,
+    chunks: VecDeque<Vec<u8>>,
+}
+
+/// Renders `template` as an iterator of chunks of `chunk_size` bytes, see [`RenderStream`].
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is 0.
+pub fn stream<T: Render>(template: T, chunk_size: usize) -> RenderStream<T> {
+    assert!(chunk_size > 0, "chunk size must be at least 1");
+    RenderStream {
+        state: State::Pending(template),
+        chunk_size,
+        chunks: VecDeque::new(),
+    }
+}
+
+struct ChunkWriter<'a> {
+    chunks: &'a mut VecDeque<Vec<u8>>,
+    chunk_size: usize,
+}
+
+impl std::fmt::Write for ChunkWriter<'_> {
+    fn write_str(&mut self, str: &str) -> std::fmt::Result {
+        let mut bytes = str.as_bytes();
+        while !bytes.is_empty() {
+            let chunk = match self.chunks.back_mut() {
+                Some(chunk) if chunk.len() < self.chunk_size => chunk,
+                _ => {
+                    self.chunks.push_back(Vec::with_capacity(self.chunk_size));
+                    self.chunks.back_mut().unwrap()
+                }
+            };
+            let len = bytes.len().min(self.chunk_size - chunk.len());
+            chunk.extend_from_slice(&bytes[..len]);
+            bytes = &bytes[len..];
+        }
+        Ok(())
+    }
+}
+
+impl<T: Render> Iterator for RenderStream<T> {
+    type Item = Result<Vec<u8>, RenderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let State::Pending(template) = &self.state {
+            let mut writer = ChunkWriter {
+                chunks: &mut self.chunks,
+                chunk_size: self.chunk_size,
+            };
+            let error = template.render(&mut writer).err();
+            self.state = State::Rendered(error);
+        }
+        if let Some(chunk) = self.chunks.pop_front() {
+            return Some(Ok(chunk));
+        }
+        match &mut self.state {
+            State::Rendered(error) => error.take().map(Err),
+            State::Pending(_) => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! Out of order streaming of slow fragments.
+//!
+//! [`suspense`] pairs a placeholder with a future producing the real fragment. When a page is
+//! rendered with [`chunks`], the first chunk is the whole page with placeholders in place of the
+//! fragments, and every following chunk fills in one fragment, in the order the futures complete.
+//! Rendered any other way, the future is awaited on the spot and the fragment is rendered inline.
+//!
+//! Slots are numbered from 0 in the order they are rendered, including slots inside fragments.
+//! The placeholder of slot `N` is written between `<template data-markup-slot="N"></template>`
+//! and `<template data-markup-slot-end="N"></template>`, and its chunk contains
+//! `<template data-markup-fill="N">...</template>` followed by `<script>markupFill(N)</script>`.
+//! The first of these chunks starts with [`SCRIPT`], which defines `markupFill` to replace the
+//! placeholder with the contents of the fill template. To swap fragments in some other way, use
+//! [`Chunks::without_script`] and define `markupFill` in the page.
+
+use crate::{Render, RenderDyn, RenderError};
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+/// Defines `markupFill(n)`, which moves the contents of the fill template of slot `n` into the
+/// document in place of its placeholder.
+pub const SCRIPT: &str = r#"<script>function markupFill(n){var q=function(a){return document.querySelector("template[data-markup-"+a+'="'+n+'"]')},s=q("slot"),e=q("slot-end"),f=q("fill");if(!s||!e||!f)return;while(s.nextSibling&&s.nextSibling!==e)s.nextSibling.remove();e.replaceWith(f.content);s.remove();f.remove()}</script>"#;
+
+type Fragment = Pin<Box<dyn Future<Output = Box<dyn RenderDyn>>>>;
+
+struct Pending {
+    id: usize,
+    fragment: Fragment,
+    fallback: Option<Box<dyn RenderDyn>>,
+}
+
+#[derive(Default)]
+struct Slots {
+    next_id: usize,
+    pending: Vec<Pending>,
+}
+
+thread_local! {
+    // Set while `Chunks` renders the page or a fragment.
+    static SLOTS: RefCell<Option<Slots>> = RefCell::new(None);
+}
+
+pub struct Suspense<P, F> {
+    placeholder: P,
+    fragment: Cell<Option<F>>,
+    fallback: Cell<Option<Box<dyn RenderDyn>>>,
+}
+
+/// Renders `placeholder` until `fragment` completes, then the fragment it produced. The future
+/// is taken by the first render; rendering again only writes the placeholder.
+pub fn suspense<P, F>(placeholder: P, fragment: F) -> Suspense<P, F>
+where
+    P: Render,
+    F: Future + 'static,
+    F::Output: Render + 'static,
+{
+    Suspense {
+        placeholder,
+        fragment: Cell::new(Some(fragment)),
+        fallback: Cell::new(None),
+    }
+}
+
+impl<P, F> Suspense<P, F> {
+    /// Rendered in place of the fragment if rendering the fragment fails.
+    pub fn on_error(self, fallback: impl Render + 'static) -> Self {
+        self.fallback.set(Some(Box::new(fallback)));
+        self
+    }
+}
+
+impl<P, F> Render for Suspense<P, F>
+where
+    P: Render,
+    F: Future + 'static,
+    F::Output: Render + 'static,
+{
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        let fragment = match self.fragment.take() {
+            Some(fragment) => fragment,
+            None => return self.placeholder.render(writer),
+        };
+        let fallback = self.fallback.take();
+        if SLOTS.with(|slots| slots.borrow().is_some()) {
+            let fragment: Fragment =
+                Box::pin(async move { Box::new(fragment.await) as Box<dyn RenderDyn> });
+            let id = SLOTS.with(|slots| {
+                let mut slots = slots.borrow_mut();
+                let slots = slots.as_mut().unwrap();
+                let id = slots.next_id;
+                slots.next_id += 1;
+                slots.pending.push(Pending {
+                    id,
+                    fragment,
+                    fallback,
+                });
+                id
+            });
+            write!(writer, r#"<template data-markup-slot="{}"></template>"#, id)?;
+            self.placeholder.render(writer)?;
+            write!(
+                writer,
+                r#"<template data-markup-slot-end="{}"></template>"#,
+                id
+            )?;
+            return Ok(());
+        }
+        let output = block_on(Box::pin(fragment));
+        match fallback {
+            Some(fallback) => {
+                let mut string = String::new();
+                match output.render(&mut string) {
+                    Ok(()) => writer.write_str(&string)?,
+                    Err(_) => fallback.render_dyn(writer)?,
+                }
+                Ok(())
+            }
+            None => output.render(writer),
+        }
+    }
+}
+
+struct ThreadWaker(std::thread::Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn waker() -> Waker {
+    Arc::new(ThreadWaker(std::thread::current())).into()
+}
+
+fn block_on<F: Future>(mut future: Pin<Box<F>>) -> F::Output {
+    let waker = waker();
+    let mut context = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+        std::thread::park();
+    }
+}
+
+// Renders with suspended fragments collected into `slots` instead of awaited.
+fn collect(
+    slots: &mut Slots,
+    render: impl FnOnce(&mut String) -> Result<(), RenderError>,
+) -> Result<String, RenderError> {
+    let previous = SLOTS.with(|cell| cell.replace(Some(std::mem::take(slots))));
+    let mut string = String::new();
+    let result = render(&mut string);
+    *slots = SLOTS
+        .with(|cell| std::mem::replace(&mut *cell.borrow_mut(), previous))
+        .unwrap();
+    result.map(|()| string)
+}
+
+/// An iterator over the chunks of a page rendered with suspended fragments, see the
+/// [module documentation](self).
+///
+/// Each call to `next` blocks until the next chunk is ready. Futures are polled on the calling
+/// thread. If a fragment fails to render and has no [`Suspense::on_error`] fallback, its
+/// placeholder stays and the error is returned in place of its chunk; the remaining slots still
+/// follow.
+pub struct Chunks<T> {
+    template: Option<T>,
+    slots: Slots,
+    script: bool,
+}
+
+/// Renders `template` as a stream of chunks.
+pub fn chunks<T: Render>(template: T) -> Chunks<T> {
+    Chunks {
+        template: Some(template),
+        slots: Slots::default(),
+        script: false,
+    }
+}
+
+impl<T: Render> Chunks<T> {
+    /// Leaves out [`SCRIPT`], for pages defining their own `markupFill`.
+    pub fn without_script(mut self) -> Self {
+        self.script = true;
+        self
+    }
+
+    fn fill(
+        &mut self,
+        pending: Pending,
+        output: Box<dyn RenderDyn>,
+    ) -> Result<String, RenderError> {
+        let Pending { id, fallback, .. } = pending;
+        let html = match collect(&mut self.slots, |string| output.render_dyn(string)) {
+            Ok(html) => html,
+            Err(error) => match fallback {
+                Some(fallback) => collect(&mut self.slots, |string| fallback.render_dyn(string))?,
+                None => return Err(error),
+            },
+        };
+        let mut chunk = String::new();
+        if !self.script {
+            self.script = true;
+            chunk.push_str(SCRIPT);
+        }
+        chunk.push_str(&format!(
+            r#"<template data-markup-fill="{0}">{1}</template><script>markupFill({0})</script>"#,
+            id, html
+        ));
+        Ok(chunk)
+    }
+}
+
+impl<T: Render> Iterator for Chunks<T> {
+    type Item = Result<String, RenderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(template) = self.template.take() {
+            return Some(collect(&mut self.slots, |string| template.render(string)));
+        }
+        let waker = waker();
+        let mut context = Context::from_waker(&waker);
+        loop {
+            if self.slots.pending.is_empty() {
+                return None;
+            }
+            for index in 0..self.slots.pending.len() {
+                let pending = &mut self.slots.pending[index];
+                if let Poll::Ready(output) = pending.fragment.as_mut().poll(&mut context) {
+                    let pending = self.slots.pending.remove(index);
+                    return Some(self.fill(pending, output));
+                }
+            }
+            std::thread::park();
+        }
+    }
+}
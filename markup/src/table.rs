@@ -0,0 +1,136 @@
+//! A minimal `<table>` builder over a slice of rows.
+
+use crate::{DynRender, Render, RenderError};
+use std::borrow::Cow;
+
+type Cell<'a, R> = dyn Fn(&'a R, &mut dyn std::fmt::Write) -> Result<(), RenderError> + 'a;
+
+struct Column<'a, R> {
+    header: DynRender<'a>,
+    cell: Box<Cell<'a, R>>,
+}
+
+pub struct Table<'a, R> {
+    rows: &'a [R],
+    columns: Vec<Column<'a, R>>,
+    table_class: Option<Cow<'a, str>>,
+    thead_class: Option<Cow<'a, str>>,
+    tr_class: Option<Cow<'a, str>>,
+    td_class: Option<Cow<'a, str>>,
+    empty: Option<DynRender<'a>>,
+}
+
+impl<'a, R> Table<'a, R> {
+    pub fn new(rows: &'a [R]) -> Self {
+        Table {
+            rows,
+            columns: Vec::new(),
+            table_class: None,
+            thead_class: None,
+            tr_class: None,
+            td_class: None,
+            empty: None,
+        }
+    }
+
+    /// Adds a column with a text header. `cell` is called once per row.
+    pub fn column<V: Render + 'a>(
+        self,
+        header: impl Into<Cow<'a, str>>,
+        cell: impl Fn(&'a R) -> V + 'a,
+    ) -> Self {
+        let header = header.into();
+        self.column_with(
+            crate::new(move |mut writer| header.render(&mut writer)),
+            cell,
+        )
+    }
+
+    /// Adds a column whose header is any [`Render`] value.
+    pub fn column_with<V: Render + 'a>(
+        mut self,
+        header: impl Render + 'a,
+        cell: impl Fn(&'a R) -> V + 'a,
+    ) -> Self {
+        self.columns.push(Column {
+            header: crate::new(move |mut writer| header.render(&mut writer)),
+            cell: Box::new(move |row, mut writer| cell(row).render(&mut writer)),
+        });
+        self
+    }
+
+    pub fn table_class(mut self, class: impl Into<Cow<'a, str>>) -> Self {
+        self.table_class = Some(class.into());
+        self
+    }
+
+    pub fn thead_class(mut self, class: impl Into<Cow<'a, str>>) -> Self {
+        self.thead_class = Some(class.into());
+        self
+    }
+
+    /// The class of every `<tr>` in `<tbody>`.
+    pub fn tr_class(mut self, class: impl Into<Cow<'a, str>>) -> Self {
+        self.tr_class = Some(class.into());
+        self
+    }
+
+    pub fn td_class(mut self, class: impl Into<Cow<'a, str>>) -> Self {
+        self.td_class = Some(class.into());
+        self
+    }
+
+    /// Rendered in a single cell spanning all columns when there are no rows.
+    pub fn empty(mut self, empty: impl Render + 'a) -> Self {
+        self.empty = Some(crate::new(move |mut writer| empty.render(&mut writer)));
+        self
+    }
+}
+
+fn open(
+    writer: &mut impl std::fmt::Write,
+    name: &str,
+    class: &Option<Cow<'_, str>>,
+) -> Result<(), RenderError> {
+    write!(writer, "<{}", name)?;
+    if let Some(class) = class {
+        writer.write_str(" class=\"")?;
+        class.render(writer)?;
+        writer.write_str("\"")?;
+    }
+    writer.write_str(">")?;
+    Ok(())
+}
+
+impl<R> Render for Table<'_, R> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        open(writer, "table", &self.table_class)?;
+        open(writer, "thead", &self.thead_class)?;
+        writer.write_str("<tr>")?;
+        for column in &self.columns {
+            writer.write_str("<th>")?;
+            column.header.render(writer)?;
+            writer.write_str("</th>")?;
+        }
+        writer.write_str("</tr></thead><tbody>")?;
+        if self.rows.is_empty() {
+            if let Some(empty) = &self.empty {
+                open(writer, "tr", &self.tr_class)?;
+                write!(writer, "<td colspan=\"{}\">", self.columns.len())?;
+                empty.render(writer)?;
+                writer.write_str("</td></tr>")?;
+            }
+        }
+        for row in self.rows {
+            open(writer, "tr", &self.tr_class)?;
+            for column in &self.columns {
+                open(writer, "td", &self.td_class)?;
+                (column.cell)(row, writer)?;
+                writer.write_str("</td>")?;
+            }
+            writer.write_str("</tr>")?;
+        }
+        writer.write_str("</tbody></table>")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+//! HTTP-date and RFC 3339 formatting of `SystemTime`, without a date library.
+
+use crate::{Render, RenderAttributeValue, RenderError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// The error returned when rendering a time whose year isn't between 0 and 9999.
+#[derive(Debug, PartialEq)]
+pub struct YearOutOfRange(pub i64);
+
+impl std::fmt::Display for YearOutOfRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "year {} can't be formatted as a date", self.0)
+    }
+}
+
+impl std::error::Error for YearOutOfRange {}
+
+struct DateTime {
+    year: i64,
+    month: usize,
+    day: i64,
+    weekday: usize,
+    hour: i64,
+    minute: i64,
+    second: i64,
+}
+
+impl DateTime {
+    // The date is computed with the `civil_from_days` algorithm from
+    // http://howardhinnant.github.io/date_algorithms.html. Sub-second precision is truncated.
+    fn new(time: SystemTime) -> Result<Self, RenderError> {
+        let seconds = match time.duration_since(UNIX_EPOCH) {
+            Ok(after) => after.as_secs() as i64,
+            Err(error) => {
+                let before = error.duration();
+                -(before.as_secs() as i64) - i64::from(before.subsec_nanos() > 0)
+            }
+        };
+        let days = seconds.div_euclid(86400);
+        let time = seconds.rem_euclid(86400);
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        if !(0..=9999).contains(&year) {
+            return Err(RenderError::wrap(YearOutOfRange(year)));
+        }
+        Ok(DateTime {
+            year,
+            month: month as usize,
+            day,
+            weekday: (days + 4).rem_euclid(7) as usize,
+            hour: time / 3600,
+            minute: time / 60 % 60,
+            second: time % 60,
+        })
+    }
+}
+
+pub struct HttpDate(SystemTime);
+
+/// Renders `time` as an HTTP-date (RFC 7231), e.g. `Tue, 15 Nov 1994 08:12:31 GMT`.
+///
+/// Rendering fails with [`YearOutOfRange`] for times before year 0 or after year 9999.
+pub fn httpdate(time: SystemTime) -> HttpDate {
+    HttpDate(time)
+}
+
+impl Render for HttpDate {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        let date = DateTime::new(self.0)?;
+        write!(
+            writer,
+            "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+            WEEKDAYS[date.weekday],
+            date.day,
+            MONTHS[date.month - 1],
+            date.year,
+            date.hour,
+            date.minute,
+            date.second
+        )?;
+        Ok(())
+    }
+}
+
+impl RenderAttributeValue for HttpDate {}
+
+pub struct Rfc3339(SystemTime);
+
+/// Renders `time` in UTC as an RFC 3339 timestamp with whole seconds, e.g.
+/// `1994-11-15T08:12:31Z`.
+///
+/// Rendering fails with [`YearOutOfRange`] for times before year 0 or after year 9999.
+pub fn rfc3339(time: SystemTime) -> Rfc3339 {
+    Rfc3339(time)
+}
+
+impl Render for Rfc3339 {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        let date = DateTime::new(self.0)?;
+        write!(
+            writer,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            date.year, date.month, date.day, date.hour, date.minute, date.second
+        )?;
+        Ok(())
+    }
+}
+
+impl RenderAttributeValue for Rfc3339 {}
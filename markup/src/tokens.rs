@@ -0,0 +1,92 @@
+use crate::{Render, RenderAttributeValue, RenderError};
+
+/// An item of [`tokens`]. `None` items are skipped.
+pub trait Token {
+    fn token(&self) -> Option<&str>;
+}
+
+impl Token for str {
+    #[inline]
+    fn token(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl Token for String {
+    #[inline]
+    fn token(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl<T: Token + ?Sized> Token for &T {
+    #[inline]
+    fn token(&self) -> Option<&str> {
+        T::token(self)
+    }
+}
+
+impl<T: Token> Token for Option<T> {
+    #[inline]
+    fn token(&self) -> Option<&str> {
+        self.as_ref().and_then(T::token)
+    }
+}
+
+pub struct Tokens<T>(Vec<T>);
+
+/// Renders a space separated list of tokens, e.g. for `rel` or `aria-labelledby`.
+///
+/// Each item is trimmed, and `None` and empty items are skipped. If no tokens are left, the
+/// attribute is omitted. An item containing whitespace after trimming would silently turn into
+/// multiple tokens, so rendering fails with an [`InvalidToken`] error instead.
+pub fn tokens<I>(iter: I) -> Tokens<I::Item>
+where
+    I: IntoIterator,
+    I::Item: Token,
+{
+    Tokens(iter.into_iter().collect())
+}
+
+impl<T: Token> Tokens<T> {
+    fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0
+            .iter()
+            .filter_map(|token| token.token())
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+    }
+}
+
+impl<T: Token> Render for Tokens<T> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+        for (index, token) in self.iter().enumerate() {
+            if token.contains(char::is_whitespace) {
+                return Err(RenderError::wrap(InvalidToken(token.to_string())));
+            }
+            if index > 0 {
+                writer.write_str(" ")?;
+            }
+            token.render(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Token> RenderAttributeValue for Tokens<T> {
+    #[inline]
+    fn is_none(&self) -> bool {
+        self.iter().next().is_none()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InvalidToken(pub String);
+
+impl std::fmt::Display for InvalidToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "token {:?} contains whitespace", self.0)
+    }
+}
+
+impl std::error::Error for InvalidToken {}
@@ -0,0 +1,34 @@
+#![cfg(feature = "anyhow")]
+
+use anyhow::Context;
+use std::num::ParseIntError;
+
+fn count(input: &str) -> anyhow::Result<u32> {
+    let count = input
+        .parse::<u32>()
+        .with_context(|| format!("parsing count {:?}", input))?;
+    Ok(count)
+}
+
+#[test]
+fn propagate() {
+    let template = markup::new(|writer| {
+        let count = count("many").context("loading widget")?;
+        write!(writer, "{}", count)?;
+        Ok(())
+    });
+    let mut string = String::new();
+    let error = markup::Render::render(&template, &mut string).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        r#"loading widget: parsing count "many": invalid digit found in string"#
+    );
+
+    let mut source = std::error::Error::source(&error);
+    let mut root = None;
+    while let Some(error) = source {
+        root = error.downcast_ref::<ParseIntError>().or(root);
+        source = error.source();
+    }
+    assert_eq!(root, "many".parse::<u32>().err().as_ref());
+}
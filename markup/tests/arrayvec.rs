@@ -0,0 +1,29 @@
+#![cfg(feature = "arrayvec")]
+
+use arrayvec::ArrayString;
+use markup::CapacityExceeded;
+
+#[test]
+fn render_string() {
+    let name = ArrayString::<16>::from("<Ferris>").unwrap();
+    assert_eq!(
+        markup::new!(p[title = &name] { @name }).to_string(),
+        r#"<p title="&lt;Ferris&gt;">&lt;Ferris&gt;</p>"#
+    );
+}
+
+#[test]
+fn render_into() {
+    let string: ArrayString<16> =
+        markup::render_to_array_string(markup::new!(p { "a & b" })).unwrap();
+    assert_eq!(string.as_str(), "<p>a &amp; b</p>");
+
+    let result: Result<ArrayString<15>, _> =
+        markup::render_to_array_string(markup::new!(p { "a & b" }));
+    let error = result.unwrap_err();
+    let source = std::error::Error::source(&error).unwrap();
+    assert_eq!(
+        source.downcast_ref::<CapacityExceeded>(),
+        Some(&CapacityExceeded { capacity: 15 })
+    );
+}
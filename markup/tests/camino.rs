@@ -0,0 +1,29 @@
+#![cfg(feature = "camino")]
+
+use camino::{Utf8Path, Utf8PathBuf};
+
+#[test]
+fn body() {
+    let path = Utf8Path::new("docs/<a> & \"b\".md");
+    assert_eq!(
+        markup::new!(code { @path }).to_string(),
+        markup::new!(code { @path.as_str() }).to_string()
+    );
+    assert_eq!(
+        markup::new!(@path).to_string(),
+        "docs/&lt;a&gt; &amp; &quot;b&quot;.md"
+    );
+}
+
+#[test]
+fn attribute() {
+    let path = &Utf8PathBuf::from("/static/a & b.css");
+    assert_eq!(
+        markup::new!(link[rel = "stylesheet", href = path];).to_string(),
+        markup::new!(link[rel = "stylesheet", href = path.as_str()];).to_string()
+    );
+    assert_eq!(
+        markup::new!(link[href = path.as_path()];).to_string(),
+        r#"<link href="/static/a &amp; b.css">"#
+    );
+}
@@ -0,0 +1,18 @@
+#![cfg(feature = "chrono")]
+
+use chrono::{FixedOffset, TimeZone, Utc};
+use markup::feed::{Atom, Entry};
+
+#[test]
+fn feed_timestamps() {
+    let updated = Utc.with_ymd_and_hms(2023, 1, 2, 3, 4, 5).unwrap();
+    let published = FixedOffset::east_opt(3600)
+        .unwrap()
+        .with_ymd_and_hms(2023, 1, 1, 0, 0, 0)
+        .unwrap();
+    let feed = Atom::new("Blog", "urn:uuid:1", updated)
+        .entry(Entry::new("Post", "urn:uuid:2", updated).published(published));
+    let string = markup::new!(@feed).to_string();
+    assert!(string.contains("<updated>2023-01-02T03:04:05+00:00</updated>"));
+    assert!(string.contains("<published>2023-01-01T00:00:00+01:00</published>"));
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "debug-comments")]
+
+markup::define! {
+    Page(title: &'static str) {
+        html {
+            head {
+                title { @Title { title } }
+                script { @Script {} }
+            }
+            body[class = Attribute(Class {})] {
+                @Header { title }
+            }
+        }
+    }
+    Header(title: &'static str) {
+        header { h1 { @Title { title } } }
+    }
+    Title(title: &'static str) { @title }
+    Script { "let a = 1;" }
+    Class { "main" }
+}
+
+struct Attribute<T>(T);
+
+impl<T: markup::Render> markup::Render for Attribute<T> {
+    fn render(&self, writer: &mut impl std::fmt::Write) -> Result<(), markup::RenderError> {
+        self.0.render(writer)
+    }
+}
+
+impl<T: markup::Render> markup::RenderAttributeValue for Attribute<T> {}
+
+#[test]
+fn nesting() {
+    assert_eq!(
+        Page { title: "Home" }.to_string(),
+        "<!-- markup:start Page -->\
+        <html><head><title>Home</title><script>let a = 1;</script></head>\
+        <body class=\"main\">\
+        <!-- markup:start Header -->\
+        <header><h1><!-- markup:start Title -->Home<!-- markup:end Title --></h1></header>\
+        <!-- markup:end Header -->\
+        </body></html>\
+        <!-- markup:end Page -->"
+    );
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "dev-reload")]
+
+use std::path::PathBuf;
+use std::sync::Once;
+
+fn dir() -> PathBuf {
+    static INIT: Once = Once::new();
+    let dir = std::env::temp_dir().join(format!("markup-dev-reload-{}", std::process::id()));
+    INIT.call_once(|| {
+        std::fs::create_dir_all(&dir).unwrap();
+        std::env::set_var(markup::dev_reload::OVERRIDE_DIR, &dir);
+    });
+    dir
+}
+
+pub struct Opaque;
+
+markup::define! {
+    Hit<'a>(title: &'a str, count: u32, opaque: Opaque) {
+        h1 { @title }
+        @let _ = (count, opaque);
+    }
+    Malformed(title: &'static str) {
+        h1 { @title }
+    }
+    UnknownField(title: &'static str) {
+        h1 { @title }
+    }
+    Unrenderable(opaque: Opaque) {
+        @let _ = opaque;
+        h1 { "compiled" }
+    }
+    Missing(title: &'static str) {
+        h1 { @title }
+    }
+}
+
+#[test]
+fn hit() {
+    std::fs::write(
+        dir().join("Hit.markup"),
+        r#"
+            // Overridden.
+            div #main.card.{count}[data-title = {title}, hidden] {
+                h2 { "Title: " {title} }
+                p { @count " items & more" }
+                br;
+            }
+        "#,
+    )
+    .unwrap();
+    let hit = Hit {
+        title: "<Hi>",
+        count: 3,
+        opaque: Opaque,
+    };
+    assert_eq!(
+        hit.to_string(),
+        concat!(
+            r#"<div id="main" class="card 3" data-title="&lt;Hi&gt;" hidden>"#,
+            "<h2>Title: &lt;Hi&gt;</h2><p>3 items &amp; more</p><br></div>"
+        )
+    );
+}
+
+#[test]
+fn malformed() {
+    std::fs::write(dir().join("Malformed.markup"), r#"div { "unterminated }"#).unwrap();
+    assert_eq!(Malformed { title: "Hi" }.to_string(), "<h1>Hi</h1>");
+}
+
+#[test]
+fn unknown_field() {
+    std::fs::write(dir().join("UnknownField.markup"), "p { {nope} }").unwrap();
+    assert_eq!(UnknownField { title: "Hi" }.to_string(), "<h1>Hi</h1>");
+
+    // Fields which don't implement `Render` can't be used.
+    std::fs::write(dir().join("Unrenderable.markup"), "p { {opaque} }").unwrap();
+    assert_eq!(
+        Unrenderable { opaque: Opaque }.to_string(),
+        "<h1>compiled</h1>"
+    );
+}
+
+#[test]
+fn missing() {
+    dir();
+    assert_eq!(Missing { title: "Hi" }.to_string(), "<h1>Hi</h1>");
+}
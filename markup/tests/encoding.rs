@@ -0,0 +1,61 @@
+#![cfg(feature = "encoding")]
+
+use encoding_rs::{ISO_2022_JP, WINDOWS_1252};
+use markup::{render_encoded, EncodingWriter};
+use std::fmt::Write;
+
+#[test]
+fn windows_1252() {
+    let title = "Café — 東京";
+    let mut bytes = Vec::new();
+    render_encoded(
+        markup::new!(p[title = title] { @title }),
+        &mut bytes,
+        WINDOWS_1252,
+    )
+    .unwrap();
+    assert_eq!(
+        bytes,
+        b"<p title=\"Caf\xe9 \x97 &#26481;&#20140;\">Caf\xe9 \x97 &#26481;&#20140;</p>"
+    );
+}
+
+#[test]
+fn escaping() {
+    let mut bytes = Vec::new();
+    render_encoded(markup::new!("<€>"), &mut bytes, WINDOWS_1252).unwrap();
+    assert_eq!(bytes, b"&lt;\x80&gt;");
+}
+
+#[test]
+fn split_writes() {
+    let mut writer = EncodingWriter::new(Vec::new(), ISO_2022_JP);
+    writer.write_str("a日").unwrap();
+    writer.write_str("本").unwrap();
+    writer.write_str("").unwrap();
+    writer.write_char('語').unwrap();
+    let bytes = writer.finish().unwrap();
+    assert_eq!(bytes, b"a\x1b$BF|K\\8l\x1b(B");
+}
+
+#[test]
+fn io_error() {
+    struct Full;
+
+    impl std::io::Write for Full {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::WriteZero.into())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let error = render_encoded(markup::new!(p { "a" }), &mut Full, WINDOWS_1252).unwrap_err();
+    let source = std::error::Error::source(&error).unwrap();
+    assert_eq!(
+        source.downcast_ref::<std::io::Error>().unwrap().kind(),
+        std::io::ErrorKind::WriteZero
+    );
+}
@@ -0,0 +1,67 @@
+use std::path::Path;
+use std::process::Command;
+
+// Builds a scratch crate with `MARKUP_DEBUG_EXPAND` set, which takes a while the first time since
+// it uses its own target directory.
+#[test]
+fn debug_expand() {
+    let dir = std::env::temp_dir().join(format!("markup-debug-expand-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            r#"
+                [package]
+                name = "markup-debug-expand"
+                version = "0.0.0"
+                edition = "2021"
+
+                [dependencies]
+                markup = {{ path = {:?} }}
+
+                [workspace]
+            "#,
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+        ),
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("src/lib.rs"),
+        r#"
+            markup::define! {
+                Greeting(name: &'static str) { p { "Hello, " @name } }
+                Ignored { "ignored" }
+            }
+
+            pub fn page() -> String {
+                markup::new!(div { @Greeting { name: "World" } }).to_string()
+            }
+        "#,
+    )
+    .unwrap();
+
+    let target = dir.join("target");
+    let output = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".into()))
+        .arg("build")
+        .arg("--quiet")
+        .current_dir(&dir)
+        .env("CARGO_TARGET_DIR", &target)
+        .env("MARKUP_DEBUG_EXPAND", "Greeting,new-1")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+
+    let expand = target.join("markup-expand");
+    let greeting = std::fs::read_to_string(expand.join("Greeting.rs")).unwrap();
+    assert!(greeting.contains("pub struct Greeting"));
+    assert!(greeting.contains("impl ::markup::Render for Greeting"));
+    assert!(stderr.contains("note: markup: wrote the expansion of `Greeting`"));
+    assert!(std::fs::read_to_string(expand.join("new-1.rs"))
+        .unwrap()
+        .contains("::markup::new"));
+    assert!(!expand.join("Ignored.rs").exists());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
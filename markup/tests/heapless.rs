@@ -0,0 +1,40 @@
+#![cfg(feature = "heapless")]
+
+use markup::CapacityExceeded;
+
+#[test]
+fn render_string() {
+    let name: heapless::String<16> = heapless::String::try_from("<Ferris>").unwrap();
+    assert_eq!(
+        markup::new!(p[title = &name] { @name }).to_string(),
+        r#"<p title="&lt;Ferris&gt;">&lt;Ferris&gt;</p>"#
+    );
+}
+
+#[test]
+fn render_vec() {
+    let mut bytes: heapless::Vec<u8, 16> = heapless::Vec::new();
+    bytes.extend_from_slice(b"<b>raw</b>").unwrap();
+    assert_eq!(markup::new!(p { @bytes }).to_string(), "<p><b>raw</b></p>");
+
+    let mut invalid: heapless::Vec<u8, 4> = heapless::Vec::new();
+    invalid.extend_from_slice(&[0xff]).unwrap();
+    let mut string = String::new();
+    assert!(markup::Render::render(&invalid, &mut string).is_err());
+}
+
+#[test]
+fn render_into() {
+    let string: heapless::String<16> =
+        markup::render_to_heapless(markup::new!(p { "a & b" })).unwrap();
+    assert_eq!(string.as_str(), "<p>a &amp; b</p>");
+
+    let result: Result<heapless::String<15>, _> =
+        markup::render_to_heapless(markup::new!(p { "a & b" }));
+    let error = result.unwrap_err();
+    let source = std::error::Error::source(&error).unwrap();
+    assert_eq!(
+        source.downcast_ref::<CapacityExceeded>(),
+        Some(&CapacityExceeded { capacity: 15 })
+    );
+}
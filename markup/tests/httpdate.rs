@@ -0,0 +1,78 @@
+#![cfg(feature = "httpdate")]
+
+use markup::{httpdate, rfc3339, YearOutOfRange};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn at(seconds: i64) -> SystemTime {
+    if seconds >= 0 {
+        UNIX_EPOCH + Duration::from_secs(seconds as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs())
+    }
+}
+
+fn render(template: impl markup::Render) -> String {
+    let mut string = String::new();
+    template.render(&mut string).unwrap();
+    string
+}
+
+#[test]
+fn known_timestamps() {
+    for (seconds, expected_httpdate, expected_rfc3339) in [
+        (0, "Thu, 01 Jan 1970 00:00:00 GMT", "1970-01-01T00:00:00Z"),
+        (
+            784887151,
+            "Tue, 15 Nov 1994 08:12:31 GMT",
+            "1994-11-15T08:12:31Z",
+        ),
+        (
+            951782400,
+            "Tue, 29 Feb 2000 00:00:00 GMT",
+            "2000-02-29T00:00:00Z",
+        ),
+        (
+            1700000000,
+            "Tue, 14 Nov 2023 22:13:20 GMT",
+            "2023-11-14T22:13:20Z",
+        ),
+    ] {
+        assert_eq!(render(httpdate(at(seconds))), expected_httpdate);
+        assert_eq!(render(rfc3339(at(seconds))), expected_rfc3339);
+    }
+    let time = at(784887151) + Duration::from_millis(999);
+    assert_eq!(render(rfc3339(time)), "1994-11-15T08:12:31Z");
+}
+
+#[test]
+fn before_epoch() {
+    assert_eq!(render(httpdate(at(-1))), "Wed, 31 Dec 1969 23:59:59 GMT");
+    assert_eq!(render(rfc3339(at(-1))), "1969-12-31T23:59:59Z");
+    let time = at(-2207520000) - Duration::from_millis(500);
+    assert_eq!(render(httpdate(time)), "Wed, 17 Jan 1900 23:59:59 GMT");
+    assert_eq!(render(rfc3339(time)), "1900-01-17T23:59:59Z");
+}
+
+#[test]
+fn year_out_of_range() {
+    let mut string = String::new();
+    let error = markup::Render::render(&httpdate(at(-62167219201)), &mut string).unwrap_err();
+    let source = std::error::Error::source(&error).unwrap();
+    assert_eq!(
+        source.downcast_ref::<YearOutOfRange>(),
+        Some(&YearOutOfRange(-1))
+    );
+    assert!(markup::Render::render(&rfc3339(at(253402300800)), &mut string).is_err());
+    assert_eq!(string, "");
+}
+
+#[test]
+fn attribute() {
+    let time = at(784887151);
+    assert_eq!(
+        markup::new!(meta["http-equiv" = "expires", content = httpdate(time)];
+            time[datetime = rfc3339(time)] {})
+        .to_string(),
+        r#"<meta http-equiv="expires" content="Tue, 15 Nov 1994 08:12:31 GMT"><time datetime="1994-11-15T08:12:31Z"></time>"#
+    );
+}
@@ -0,0 +1,30 @@
+#![cfg(feature = "mime")]
+
+use mime::Mime;
+
+#[test]
+fn simple() {
+    let mime = mime::IMAGE_PNG;
+    assert_eq!(
+        markup::new!(source[type = &mime, srcset = "a.png"]; @mime).to_string(),
+        r#"<source type="image/png" srcset="a.png">image/png"#
+    );
+}
+
+#[test]
+fn parameters() {
+    let mime = mime::TEXT_HTML_UTF_8;
+    assert_eq!(
+        markup::new!(link[rel = "alternate", type = &mime];).to_string(),
+        r#"<link rel="alternate" type="text/html; charset=utf-8">"#
+    );
+}
+
+#[test]
+fn quoted_parameter() {
+    let mime: Mime = r#"application/x-test; name="a <b> & c""#.parse().unwrap();
+    assert_eq!(
+        markup::new!(object[type = &mime] {}).to_string(),
+        r#"<object type="application/x-test; name=&quot;a &lt;b&gt; &amp; c&quot;"></object>"#
+    );
+}
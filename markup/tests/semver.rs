@@ -0,0 +1,30 @@
+#![cfg(feature = "semver")]
+
+use semver::{Version, VersionReq};
+
+#[test]
+fn version() {
+    let version = Version::parse("1.2.3").unwrap();
+    assert_eq!(
+        markup::new!(span[title = &version] { @version }).to_string(),
+        r#"<span title="1.2.3">1.2.3</span>"#
+    );
+}
+
+#[test]
+fn pre_release_and_build_metadata() {
+    let version = Version::parse("1.0.0-alpha.1+build.5-x").unwrap();
+    assert_eq!(
+        markup::new!(span[title = &version] { @version }).to_string(),
+        r#"<span title="1.0.0-alpha.1+build.5-x">1.0.0-alpha.1+build.5-x</span>"#
+    );
+}
+
+#[test]
+fn version_req() {
+    let req = VersionReq::parse(">=1, <2").unwrap();
+    assert_eq!(
+        markup::new!(td[title = &req] { @req }).to_string(),
+        r#"<td title="&gt;=1, &lt;2">&gt;=1, &lt;2</td>"#
+    );
+}
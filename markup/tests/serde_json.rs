@@ -0,0 +1,120 @@
+#![cfg(feature = "serde_json")]
+
+use serde::Serialize;
+use serde_json_crate as serde_json;
+
+#[derive(Serialize)]
+struct Article<'a> {
+    #[serde(rename = "@type")]
+    kind: &'a str,
+    headline: &'a str,
+    tags: Vec<&'a str>,
+}
+
+fn script(string: &str) -> &str {
+    string
+        .strip_prefix(r#"<script type="application/ld+json">"#)
+        .and_then(|string| string.strip_suffix("</script>"))
+        .unwrap()
+}
+
+// `\u{5c}` is a backslash: the escapes json_ld is expected to apply.
+fn escape(json: &str) -> String {
+    json.replace('<', "\u{5c}u003c")
+        .replace('>', "\u{5c}u003e")
+        .replace('&', "\u{5c}u0026")
+        .replace('\u{2028}', "\u{5c}u2028")
+}
+
+#[test]
+fn json_ld() {
+    let article = Article {
+        kind: "Article",
+        headline: "</script><script>alert(1)</script>",
+        tags: vec!["a & b", "<!--", "\u{2028}"],
+    };
+    let json_ld = markup::json_ld(&article);
+    let string = markup::new!(@json_ld).to_string();
+    assert_eq!(
+        script(&string),
+        escape(&serde_json::to_string(&article).unwrap())
+    );
+    assert_eq!(string.matches("<script").count(), 1);
+    assert_eq!(string.matches("</script").count(), 1);
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(script(&string)).unwrap(),
+        serde_json::to_value(&article).unwrap()
+    );
+}
+
+#[test]
+fn json_ld_value() {
+    let value = serde_json::json!({ "name": "Ünïcödé 日本 </script>" });
+    let json_ld = markup::json_ld(&value);
+    let string = markup::new!(@json_ld).to_string();
+    assert_eq!(script(&string), escape(&value.to_string()));
+    assert!(!script(&string).contains('<'));
+}
+
+#[test]
+fn json_ld_error() {
+    let value = std::collections::BTreeMap::from([((1, 2), "tuple keys are rejected")]);
+    let mut string = String::new();
+    let error = markup::Render::render(&markup::json_ld(&value), &mut string).unwrap_err();
+    let source = std::error::Error::source(&error).unwrap();
+    assert!(source.downcast_ref::<serde_json::Error>().is_some());
+}
+
+#[derive(Serialize)]
+struct Bootstrap<'a> {
+    user: User<'a>,
+    articles: Vec<Article<'a>>,
+}
+
+#[derive(Serialize)]
+struct User<'a> {
+    name: &'a str,
+    admin: bool,
+}
+
+#[test]
+fn json() {
+    let data = Bootstrap {
+        user: User {
+            name: "</script><!-- x",
+            admin: false,
+        },
+        articles: vec![Article {
+            kind: "Article",
+            headline: "Tom & Jerry \u{2029}",
+            tags: vec![">"],
+        }],
+    };
+    let json = markup::json(&data);
+    let string = markup::new! {
+        script[type = "application/json", id = "data"] { @json }
+    }
+    .to_string();
+    let payload = string
+        .strip_prefix(r#"<script type="application/json" id="data">"#)
+        .and_then(|string| string.strip_suffix("</script>"))
+        .unwrap();
+    assert_eq!(
+        payload,
+        escape(&serde_json::to_string(&data).unwrap()).replace('\u{2029}', "\u{5c}u2029")
+    );
+    assert!(!payload.contains(['<', '>', '&']));
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(payload).unwrap(),
+        serde_json::to_value(&data).unwrap()
+    );
+}
+
+#[test]
+fn json_error() {
+    let value = std::collections::HashMap::from([(vec![1], "sequence keys are rejected")]);
+    let mut string = String::new();
+    let error = markup::Render::render(&markup::json(&value), &mut string).unwrap_err();
+    assert!(matches!(error, markup::RenderError::Other(_)));
+    assert!(error.downcast_ref::<serde_json::Error>().is_some());
+}
@@ -0,0 +1,115 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// The number of templates in the scratch crate, all of them writing the same static strings.
+const TEMPLATES: usize = 300;
+const FRAGMENT: &str = "markup-shared-static-fragment";
+const START: &str = r#"<section class="card"><h2>"#;
+
+// Static output is written as plain string literals instead of shared constants, since rustc
+// already merges identical literals within a module. This builds a scratch crate in release mode
+// twice, once with `define!` templates and once with hand written `Display` impls writing the same
+// strings through shared constants. It checks that both print the same and that each static
+// string is in both binaries exactly once, and prints their sizes. It takes a while the first time
+// since it uses its own target directory. Run with `--nocapture` to see the sizes.
+#[test]
+fn static_literals_are_merged() {
+    let dir = std::env::temp_dir().join(format!("markup-static-literals-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("src/bin")).unwrap();
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            r#"
+                [package]
+                name = "markup-static-literals"
+                version = "0.0.0"
+                edition = "2021"
+
+                [dependencies]
+                markup = {{ path = {:?} }}
+
+                [workspace]
+            "#,
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+        ),
+    )
+    .unwrap();
+
+    let mut literals = String::from("markup::define! {\n");
+    let mut consts = format!(
+        r#"
+            const START: &str = "<section class=\"card\"><h2>";
+            const END: &str = "</h2><p class=\"footer\">{}</p></section>";
+
+            macro_rules! card {{
+                ($name:ident) => {{
+                    struct $name(String);
+
+                    impl std::fmt::Display for $name {{
+                        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {{
+                            f.write_str(START)?;
+                            f.write_str(&self.0)?;
+                            f.write_str(END)
+                        }}
+                    }}
+                }};
+            }}
+        "#,
+        FRAGMENT
+    );
+    for i in 0..TEMPLATES {
+        literals += &format!(
+            "Card{}(title: String) {{ section.card {{ h2 {{ @title }} p.footer {{ {:?} }} }} }}\n",
+            i, FRAGMENT
+        );
+        consts += &format!("card!(Card{});\n", i);
+    }
+    literals += "}\n";
+    for source in [&mut literals, &mut consts] {
+        *source += "fn main() {\n    let title = std::env::args().count().to_string();\n";
+        for i in 0..TEMPLATES {
+            *source += &format!("    println!(\"{{}}\", Card{}(title.clone()));\n", i);
+        }
+        *source += "}\n";
+    }
+    // Templates are structs with named fields.
+    literals = literals.replace("(title.clone())", " { title: title.clone() }");
+    std::fs::write(dir.join("src/bin/literals.rs"), literals).unwrap();
+    std::fs::write(dir.join("src/bin/consts.rs"), consts).unwrap();
+
+    let target = dir.join("target");
+    let output = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".into()))
+        .args(["build", "--release", "--quiet"])
+        .current_dir(&dir)
+        .env("CARGO_TARGET_DIR", &target)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let bin = |name: &str| -> PathBuf {
+        target
+            .join("release")
+            .join(format!("{}{}", name, std::env::consts::EXE_SUFFIX))
+    };
+    let run = |name: &str| Command::new(bin(name)).output().unwrap().stdout;
+    assert_eq!(run("literals"), run("consts"));
+
+    for name in ["literals", "consts"] {
+        let bytes = std::fs::read(bin(name)).unwrap();
+        for str in [START, FRAGMENT] {
+            let count = bytes
+                .windows(str.len())
+                .filter(|window| *window == str.as_bytes())
+                .count();
+            assert_eq!(count, 1, "{} in {}", str, name);
+        }
+        println!("{}: {} bytes", name, bytes.len());
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::useless_vec, non_local_definitions)]
+
 #[macro_export]
 macro_rules! t {
     ($name:ident, {$($define:tt)+}, $($eval:expr => $expect:expr,)+) => {
@@ -13,6 +15,30 @@ macro_rules! t {
     };
 }
 
+// Fails every write.
+struct FailingWriter;
+
+impl std::fmt::Write for FailingWriter {
+    fn write_str(&mut self, _: &str) -> std::fmt::Result {
+        Err(std::fmt::Error)
+    }
+}
+
+// Records the output and the number of writes.
+#[derive(Default)]
+struct CountingWrites {
+    output: String,
+    writes: usize,
+}
+
+impl std::fmt::Write for CountingWrites {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.writes += 1;
+        self.output.push_str(s);
+        Ok(())
+    }
+}
+
 t! {
     t1,
     {
@@ -387,3 +413,2453 @@ t! {
         data: [("foo", "bar"), ("baz", "quux")].iter().cloned().collect()
     } => r#"<div data-baz="quux" data-foo="bar"></div>"#,
 }
+
+#[test]
+fn render() {
+    fn email(msg: &str) -> Result<String, markup::RenderError> {
+        use std::fmt::Write;
+
+        let mut string = String::new();
+        writeln!(string, "--boundary")?;
+        markup::render!(string, {
+            div.note { @msg }
+        })?;
+        writeln!(string)?;
+        markup::render!(&mut string, { p { @msg.len() } })?;
+        write!(string, "\n--boundary--")?;
+        Ok(string)
+    }
+
+    assert_eq!(
+        email("<hi>").unwrap(),
+        "--boundary\n<div class=\"note\">&lt;hi&gt;</div>\n<p>4</p>\n--boundary--"
+    );
+
+    let mut calls = 0;
+    let mut strings = vec![String::new()];
+    markup::render!(
+        {
+            calls += 1;
+            &mut strings[0]
+        },
+        { "once" }
+    )
+    .unwrap();
+    assert_eq!(calls, 1);
+    assert_eq!(strings[0], "once");
+}
+
+#[test]
+fn new_move() {
+    fn greeting(name: &str) -> markup::DynRender<'static> {
+        let name = name.to_uppercase();
+        let count = name.len();
+        markup::new!(move {
+            p { "Hello, " @name " (" @count ")" }
+        })
+    }
+
+    assert_eq!(
+        greeting("<World>").to_string(),
+        "<p>Hello, &lt;WORLD&gt; (7)</p>"
+    );
+
+    let names = vec![String::from("a"), String::from("b")];
+    let borrowed = &names;
+    let template = markup::new! {
+        @for name in borrowed.iter() { li { @name } }
+    };
+    assert_eq!(template.to_string(), "<li>a</li><li>b</li>");
+    assert_eq!(names.len(), 2);
+}
+
+#[derive(Debug, PartialEq)]
+struct LookupError(u32);
+
+impl std::fmt::Display for LookupError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(fmt, "lookup of {} failed", self.0)
+    }
+}
+
+impl std::error::Error for LookupError {}
+
+fn lookup(id: u32) -> Result<Option<&'static str>, LookupError> {
+    match id {
+        1 => Ok(Some("<one>")),
+        2 => Ok(None),
+        _ => Err(LookupError(id)),
+    }
+}
+
+t! {
+    t_try,
+    {
+        A(id: u32) {
+            p { @lookup(*id)? }
+            a[title = lookup(*id)?] {}
+        }
+    },
+    A { id: 1 } => r#"<p>&lt;one&gt;</p><a title="&lt;one&gt;"></a>"#,
+    A { id: 2 } => "<p></p><a></a>",
+}
+
+#[test]
+fn try_error() {
+    use markup::Render;
+
+    markup::define! {
+        Text(id: u32) { p { @lookup(*id)? } }
+        Attribute(id: u32) { a[title = lookup(*id)?] { "never" } }
+    }
+
+    let mut string = String::new();
+    let error = Text { id: 3 }.render(&mut string).unwrap_err();
+    assert_eq!(error.to_string(), "lookup of 3 failed");
+    assert_eq!(string, "<p>");
+
+    let mut string = String::new();
+    let error = Attribute { id: 4 }.render(&mut string).unwrap_err();
+    match error {
+        markup::RenderError::Other(error) => {
+            assert_eq!(error.downcast_ref::<LookupError>(), Some(&LookupError(4)))
+        }
+        _ => panic!("expected RenderError::Other"),
+    }
+    assert_eq!(string, "<a");
+
+    let id = 5;
+    let template = markup::new! { @lookup(id)? };
+    assert!(template.render(&mut String::new()).is_err());
+}
+
+t! {
+    t_cfg_field,
+    {
+        A(
+            shown: u32,
+            #[cfg(any())]
+            hidden: u32,
+        ) {
+            @shown
+        }
+    },
+    A { shown: 1 } => "1",
+}
+
+t! {
+    t_dyn_write,
+    {
+        #[markup(dyn_write)]
+        #[derive(Clone)]
+        A<'a, T: markup::Render>(title: &'a str, body: T, id: Option<u32>) {
+            div[id = id] {
+                h1 { @title }
+                @body
+                @format_args!("{}", "<&>")
+                @B { n: 1 }
+            }
+        }
+        #[markup(dyn_write)]
+        B(n: u32) { @n }
+    },
+    A { title: "<Title>", body: markup::new!(p { "&" }), id: Some(1) } =>
+        r#"<div id="1"><h1>&lt;Title&gt;</h1><p>&amp;</p>&lt;&amp;&gt;1</div>"#,
+    A { title: "", body: "", id: None }.clone() => "<div><h1></h1>&lt;&amp;&gt;1</div>",
+}
+
+#[test]
+fn dyn_write_error() {
+    use markup::Render;
+
+    markup::define! {
+        #[markup(dyn_write)]
+        Text(id: u32) { p { @lookup(*id)? } }
+    }
+
+    let mut string = String::new();
+    let error = Text { id: 3 }.render(&mut string).unwrap_err();
+    assert_eq!(error.to_string(), "lookup of 3 failed");
+    assert_eq!(string, "<p>");
+
+    assert!(matches!(
+        Text { id: 1 }.render(&mut FailingWriter),
+        Err(markup::RenderError::Fmt(_))
+    ));
+}
+
+t! {
+    t_raw,
+    {
+        A(svg: String) {
+            div {
+                @raw {
+                    r#"<svg viewBox="0 0 1 1">"#
+                    "<path d='M0 0'/></svg>"
+                }
+            }
+            @raw(svg)
+            @raw("<br>")
+            span { @raw(format!("{}&{}", "<a>", "<b>")) }
+        }
+    },
+    A { svg: "<svg></svg>".into() } =>
+        r#"<div><svg viewBox="0 0 1 1"><path d='M0 0'/></svg></div><svg></svg><br><span><a>&<b></span>"#,
+}
+
+#[test]
+fn raw_static() {
+    markup::define! {
+        A {
+            div { @raw { "<svg>" "</svg>" } }
+        }
+    }
+
+    let mut writer = CountingWrites::default();
+    markup::Render::render(&A {}, &mut writer).unwrap();
+    assert_eq!(writer.writes, 1);
+    assert_eq!(writer.output, "<div><svg></svg></div>");
+}
+
+#[test]
+fn cache() {
+    use markup::cache::MemoCache;
+    use std::cell::Cell;
+
+    markup::define! {
+        Category<'a>(id: u32, name: &'a str, cache: &'a MemoCache<u32>, calls: &'a Cell<u32>) {
+            @cache(*id, cache) {
+                @let () = calls.set(calls.get() + 1);
+                div { @name }
+            }
+            @id
+        }
+    }
+
+    let cache = MemoCache::new();
+    let calls = Cell::new(0);
+    let render = |id, name| {
+        Category {
+            id,
+            name,
+            cache: &cache,
+            calls: &calls,
+        }
+        .to_string()
+    };
+    assert_eq!(render(1, "<a>"), "<div>&lt;a&gt;</div>1");
+    assert_eq!(render(1, "b"), "<div>&lt;a&gt;</div>1");
+    assert_eq!(calls.get(), 1);
+    assert_eq!(render(2, "c"), "<div>c</div>2");
+    assert_eq!(calls.get(), 2);
+}
+
+#[test]
+fn cache_error() {
+    use markup::cache::MemoCache;
+    use markup::Render;
+
+    let cache = MemoCache::new();
+    let template = |id| {
+        let cache = &cache;
+        markup::new! {
+            @cache("key", cache) { p { @lookup(id)? } }
+        }
+    };
+
+    let mut string = String::new();
+    assert!(template(3).render(&mut string).is_err());
+    assert_eq!(string, "");
+    assert_eq!(template(1).to_string(), "<p>&lt;one&gt;</p>");
+    assert_eq!(template(3).to_string(), "<p>&lt;one&gt;</p>");
+}
+
+t! {
+    t_pre,
+    {
+        A {
+            pre {
+                code {
+                    @pre {
+                        r#"
+                        fn main() {
+                            let x = "<&>";
+
+                            println!("{}", x);
+                        }
+                        "#
+                    }
+                }
+            }
+        }
+    },
+    A {} => "<pre><code>fn main() {\n    let x = &quot;&lt;&amp;&gt;&quot;;\n\n    println!(&quot;{}&quot;, x);\n}</code></pre>",
+}
+
+t! {
+    t_tokens,
+    {
+        A<'a>(rel: Vec<Option<&'a str>>, labels: &'a [String]) {
+            a[rel = markup::tokens(rel), "aria-labelledby" = markup::tokens(*labels)] {}
+            iframe[sandbox = markup::tokens([Some(Some(" allow-forms ")), Some(None), None])] {}
+        }
+    },
+    A {
+        rel: vec![Some("noopener"), None, Some(""), Some("<x>")],
+        labels: &["a".to_string(), " b ".to_string()],
+    } => r#"<a rel="noopener &lt;x&gt;" aria-labelledby="a b"></a><iframe sandbox="allow-forms"></iframe>"#,
+    A { rel: vec![None, Some(" ")], labels: &[] } => r#"<a></a><iframe sandbox="allow-forms"></iframe>"#,
+}
+
+#[test]
+fn tokens_whitespace() {
+    use markup::Render;
+
+    let template = markup::new! { a[rel = markup::tokens(["noopener", "no referrer"])] {} };
+    let error = template.render(&mut String::new()).unwrap_err();
+    match error {
+        markup::RenderError::Other(error) => assert_eq!(
+            error.downcast_ref(),
+            Some(&markup::InvalidToken("no referrer".into()))
+        ),
+        _ => panic!("expected RenderError::Other"),
+    }
+}
+
+t! {
+    t_meta,
+    {
+        Head<'a>(description: &'a str) {
+            @markup::meta::og()
+                .title("Title")
+                .description(*description)
+                .image("https://example.com/a.png?w=1&h=2")
+                .url(String::from("https://example.com/"))
+                .site_name("Example")
+                .custom("og:video", "https://example.com/a.mp4")
+                .title("New Title")
+            @markup::meta::twitter()
+                .card(markup::meta::Card::SummaryLargeImage)
+                .creator("@example")
+        }
+        Partial {
+            @markup::meta::og().title("Title")
+            @markup::meta::twitter()
+        }
+    },
+    Head { description: r#"Say "hi" & <bye>"# } => concat!(
+        r#"<meta property="og:title" content="New Title">"#,
+        r#"<meta property="og:description" content="Say &quot;hi&quot; &amp; &lt;bye&gt;">"#,
+        r#"<meta property="og:image" content="https://example.com/a.png?w=1&amp;h=2">"#,
+        r#"<meta property="og:url" content="https://example.com/">"#,
+        r#"<meta property="og:site_name" content="Example">"#,
+        r#"<meta property="og:video" content="https://example.com/a.mp4">"#,
+        r#"<meta name="twitter:card" content="summary_large_image">"#,
+        r#"<meta name="twitter:creator" content="@example">"#,
+    ),
+    Partial {} => r#"<meta property="og:title" content="Title">"#,
+}
+
+t! {
+    t_head,
+    {
+        Layout<'a>(css: &'a str) {
+            head {
+                @markup::head::stylesheet(*css)
+                @markup::head::preload("/font.woff2", markup::head::As::Font).mime("font/woff2")
+                @markup::head::preload("/app.js", markup::head::As::Script)
+                @markup::head::preload("/a.png", markup::head::As::Image).crossorigin()
+                @markup::head::prefetch("/next")
+                @markup::head::preconnect("https://cdn.example.com")
+                @markup::head::icon("/icon.png", "32x32", "image/png")
+                @markup::head::canonical(format!("https://example.com/?a={}&b={}", 1, 2))
+            }
+        }
+    },
+    Layout { css: "/main.css?v=\"1\"" } => concat!(
+        "<head>",
+        r#"<link rel="stylesheet" href="/main.css?v=&quot;1&quot;">"#,
+        r#"<link rel="preload" href="/font.woff2" as="font" type="font/woff2" crossorigin>"#,
+        r#"<link rel="preload" href="/app.js" as="script">"#,
+        r#"<link rel="preload" href="/a.png" as="image" crossorigin>"#,
+        r#"<link rel="prefetch" href="/next">"#,
+        r#"<link rel="preconnect" href="https://cdn.example.com">"#,
+        r#"<link rel="icon" href="/icon.png" type="image/png" sizes="32x32">"#,
+        r#"<link rel="canonical" href="https://example.com/?a=1&amp;b=2">"#,
+        "</head>",
+    ),
+}
+
+#[test]
+fn feed() {
+    use markup::feed::{Atom, Entry};
+
+    markup::define! {
+        Post<'a>(body: &'a str) {
+            p { @body }
+        }
+    }
+
+    let feed = Atom::new("Blog & Co", "urn:uuid:1", "2023-01-02T03:04:05Z")
+        .author("Alice")
+        .self_link("https://example.com/feed.xml?a=1&b=2")
+        .entry(
+            Entry::new("First <post>", "urn:uuid:2", "2023-01-01T00:00:00Z")
+                .link("https://example.com/1")
+                .content_html(Post {
+                    body: "1 < 2 & \"3\"",
+                }),
+        )
+        .entry(Entry::new(
+            "Second",
+            "urn:uuid:3",
+            String::from("2023-01-02T00:00:00Z"),
+        ));
+
+    let string = markup::new!(@feed).to_string();
+    assert_eq!(
+        string,
+        concat!(
+            r#"<?xml version="1.0" encoding="utf-8"?><feed xmlns="http://www.w3.org/2005/Atom">"#,
+            "<title>Blog &amp; Co</title><id>urn:uuid:1</id><updated>2023-01-02T03:04:05Z</updated>",
+            r#"<link href="https://example.com/feed.xml?a=1&amp;b=2" rel="self"/>"#,
+            "<author><name>Alice</name></author>",
+            "<entry><title>First &lt;post&gt;</title><id>urn:uuid:2</id>",
+            "<updated>2023-01-01T00:00:00Z</updated>",
+            r#"<link href="https://example.com/1" rel="alternate"/>"#,
+            r#"<content type="html">&lt;p&gt;1 &amp;lt; 2 &amp;amp; &amp;quot;3&amp;quot;&lt;/p&gt;</content>"#,
+            "</entry>",
+            "<entry><title>Second</title><id>urn:uuid:3</id><updated>2023-01-02T00:00:00Z</updated></entry>",
+            "</feed>"
+        )
+    );
+
+    let document = roxmltree::Document::parse(&string).unwrap();
+    let root = document.root_element();
+    assert_eq!(
+        root.tag_name().namespace(),
+        Some("http://www.w3.org/2005/Atom")
+    );
+    let entries = root
+        .children()
+        .filter(|node| node.has_tag_name("entry"))
+        .collect::<Vec<_>>();
+    assert_eq!(entries.len(), 2);
+    for entry in &entries {
+        for name in ["title", "id", "updated"] {
+            assert!(entry.children().any(|node| node.has_tag_name(name)));
+        }
+    }
+    let content = entries[0]
+        .children()
+        .find(|node| node.has_tag_name("content"))
+        .unwrap();
+    assert_eq!(
+        content.text(),
+        Some(r#"<p>1 &lt; 2 &amp; &quot;3&quot;</p>"#)
+    );
+}
+
+#[test]
+fn sitemap() {
+    use markup::sitemap::{sitemap, url, Changefreq, SitemapIndex, UrlSet};
+
+    let urls = UrlSet::new()
+        .url(
+            url("https://example.com/?a=1&b=2")
+                .lastmod("2023-01-02")
+                .changefreq(Changefreq::Weekly)
+                .priority(0.8),
+        )
+        .url(url("https://example.com/about").priority(3.0))
+        .url(url("https://example.com/empty").priority(f32::NAN));
+    assert_eq!(
+        markup::new!(@urls).to_string(),
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#,
+            "<url><loc>https://example.com/?a=1&amp;b=2</loc><lastmod>2023-01-02</lastmod>",
+            "<changefreq>weekly</changefreq><priority>0.8</priority></url>",
+            "<url><loc>https://example.com/about</loc><priority>1.0</priority></url>",
+            "<url><loc>https://example.com/empty</loc></url>",
+            "</urlset>"
+        )
+    );
+
+    let index = SitemapIndex::new()
+        .sitemap(sitemap("https://example.com/sitemap-1.xml?page=1&size=10").lastmod("2023-01-02"))
+        .sitemap(sitemap("https://example.com/sitemap-2.xml"));
+    assert_eq!(
+        markup::new!(@index).to_string(),
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#,
+            "<sitemap><loc>https://example.com/sitemap-1.xml?page=1&amp;size=10</loc>",
+            "<lastmod>2023-01-02</lastmod></sitemap>",
+            "<sitemap><loc>https://example.com/sitemap-2.xml</loc></sitemap>",
+            "</sitemapindex>"
+        )
+    );
+}
+
+#[test]
+fn sitemap_too_many_urls() {
+    use markup::sitemap::{url, TooManyEntries, UrlSet, MAX_ENTRIES};
+
+    let urls = (0..=MAX_ENTRIES)
+        .map(|_| url("https://example.com/"))
+        .collect::<UrlSet>();
+    let mut string = String::new();
+    let error = markup::Render::render(&urls, &mut string).unwrap_err();
+    let source = std::error::Error::source(&error).unwrap();
+    assert_eq!(
+        source.downcast_ref::<TooManyEntries>().unwrap().0,
+        MAX_ENTRIES + 1
+    );
+    assert!(string.is_empty());
+}
+
+#[test]
+fn registry() {
+    use markup::registry::{Registry, UnknownTemplate};
+    use markup::RenderDyn;
+
+    struct PageCtx {
+        title: String,
+        price: u32,
+    }
+
+    markup::define! {
+        ArticleView<'a>(title: &'a str) {
+            article { h1 { @title } }
+        }
+        ProductView(price: u32) {
+            p.price { @price }
+        }
+    }
+
+    let mut registry = Registry::<PageCtx>::new();
+    registry
+        .insert("article", |ctx: &PageCtx| {
+            Box::new(ArticleView { title: &ctx.title })
+        })
+        .insert("product", |ctx: &PageCtx| {
+            Box::new(ProductView { price: ctx.price }) as Box<dyn RenderDyn>
+        });
+    assert!(registry.contains("article"));
+    let mut names = registry.names().collect::<Vec<_>>();
+    names.sort_unstable();
+    assert_eq!(names, ["article", "product"]);
+
+    let ctx = PageCtx {
+        title: "<Hello>".into(),
+        price: 42,
+    };
+    let render = |name: &str| {
+        let mut string = String::new();
+        registry.render(name, &ctx, &mut string).map(|()| string)
+    };
+    assert_eq!(
+        render("article").unwrap(),
+        "<article><h1>&lt;Hello&gt;</h1></article>"
+    );
+    assert_eq!(render("product").unwrap(), r#"<p class="price">42</p>"#);
+
+    let error = render("landing").unwrap_err();
+    let source = std::error::Error::source(&error).unwrap();
+    assert_eq!(
+        source.downcast_ref::<UnknownTemplate>(),
+        Some(&UnknownTemplate("landing".into()))
+    );
+    assert_eq!(error.to_string(), r#"no template registered as "landing""#);
+
+    let template = registry.get("product", &ctx).unwrap();
+    assert_eq!(
+        markup::new!(div { @template }).to_string(),
+        "<div><p class=\"price\">42</p></div>"
+    );
+}
+
+#[derive(Debug)]
+struct RowError(usize);
+
+impl std::fmt::Display for RowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "row {} failed", self.0)
+    }
+}
+
+impl std::error::Error for RowError {}
+
+fn rows(fail_at: Option<usize>) -> impl Iterator<Item = Result<usize, RowError>> {
+    (1..=4).map(move |n| {
+        if Some(n) == fail_at {
+            Err(RowError(n))
+        } else {
+            Ok(n)
+        }
+    })
+}
+
+#[test]
+fn try_iter() {
+    let mut string = String::new();
+    let error = markup::Render::render(&markup::try_iter(rows(Some(3))), &mut string).unwrap_err();
+    assert_eq!(string, "12");
+    let source = std::error::Error::source(&error).unwrap();
+    assert_eq!(source.downcast_ref::<RowError>().unwrap().0, 3);
+
+    let items = &markup::try_iter(rows(None));
+    assert_eq!(
+        markup::new!(@items).to_string(),
+        markup::new!(@for n in rows(None).map(Result::unwrap) { @n }).to_string()
+    );
+    let mut string = String::new();
+    markup::Render::render(items, &mut string).unwrap();
+    assert_eq!(string, "");
+}
+
+markup::define! {
+    TryFor(fail_at: Option<usize>) {
+        ul {
+            @for (i, n)? in rows(*fail_at).map(|row| row.map(|n| (n, n * 10))) {
+                li { @i ": " @n }
+            }
+        }
+    }
+}
+
+#[test]
+fn try_for() {
+    assert_eq!(
+        TryFor { fail_at: None }.to_string(),
+        "<ul><li>1: 10</li><li>2: 20</li><li>3: 30</li><li>4: 40</li></ul>"
+    );
+    let mut string = String::new();
+    let error = markup::Render::render(&TryFor { fail_at: Some(3) }, &mut string).unwrap_err();
+    assert_eq!(string, "<ul><li>1: 10</li><li>2: 20</li>");
+    assert_eq!(error.to_string(), "row 3 failed");
+}
+
+#[test]
+fn table() {
+    use markup::table::Table;
+
+    struct Row {
+        name: String,
+        price: u32,
+    }
+
+    markup::define! {
+        Price(cents: u32) {
+            span.price { @{cents / 100} "." @format!("{:02}", cents % 100) }
+        }
+    }
+
+    let rows = vec![
+        Row {
+            name: "Tea & Biscuits".into(),
+            price: 350,
+        },
+        Row {
+            name: "<Coffee>".into(),
+            price: 1205,
+        },
+    ];
+    let table = Table::new(&rows)
+        .column("Name", |row: &Row| &row.name)
+        .column_with(markup::new!(abbr[title = "in EUR"] { "Price" }), |row| {
+            Price { cents: row.price }
+        });
+    assert_eq!(
+        markup::new!(@table).to_string(),
+        concat!(
+            "<table><thead><tr><th>Name</th>",
+            r#"<th><abbr title="in EUR">Price</abbr></th></tr></thead><tbody>"#,
+            r#"<tr><td>Tea &amp; Biscuits</td><td><span class="price">3.50</span></td></tr>"#,
+            r#"<tr><td>&lt;Coffee&gt;</td><td><span class="price">12.05</span></td></tr>"#,
+            "</tbody></table>"
+        )
+    );
+
+    let table = Table::new(&rows[..1])
+        .column("Name", |row: &Row| &row.name)
+        .table_class("table striped")
+        .thead_class("head")
+        .tr_class("row")
+        .td_class("cell \"x\"");
+    assert_eq!(
+        markup::new!(@table).to_string(),
+        concat!(
+            r#"<table class="table striped"><thead class="head"><tr><th>Name</th></tr></thead>"#,
+            r#"<tbody><tr class="row"><td class="cell &quot;x&quot;">Tea &amp; Biscuits</td></tr>"#,
+            "</tbody></table>"
+        )
+    );
+
+    let empty: Vec<Row> = Vec::new();
+    let table = Table::new(&empty)
+        .column("Name", |row: &Row| &row.name)
+        .column("Price", |row: &Row| row.price);
+    let mut string = String::new();
+    markup::Render::render(&table, &mut string).unwrap();
+    assert_eq!(
+        string,
+        "<table><thead><tr><th>Name</th><th>Price</th></tr></thead><tbody></tbody></table>"
+    );
+    let table = table.empty(markup::new!(em { "Nothing here" }));
+    assert_eq!(
+        markup::new!(@table).to_string(),
+        concat!(
+            "<table><thead><tr><th>Name</th><th>Price</th></tr></thead><tbody>",
+            r#"<tr><td colspan="2"><em>Nothing here</em></td></tr>"#,
+            "</tbody></table>"
+        )
+    );
+}
+
+#[test]
+fn select() {
+    let countries = [("de", "Germany"), ("fr", "France"), ("it", "Italy")];
+
+    let select = markup::select("country")
+        .options(countries)
+        .selected(Some("fr"));
+    assert_eq!(
+        markup::new!(@select).to_string(),
+        concat!(
+            r#"<select name="country"><option value="de">Germany</option>"#,
+            r#"<option value="fr" selected>France</option><option value="it">Italy</option>"#,
+            "</select>"
+        )
+    );
+
+    let select = markup::select("country")
+        .options(countries)
+        .selected(None::<&str>);
+    assert!(!markup::new!(@select).to_string().contains("selected"));
+
+    let select = markup::select("country")
+        .placeholder("Choose…")
+        .options(countries)
+        .selected(None::<String>)
+        .attrs([("id", "country"), ("required", "")]);
+    assert_eq!(
+        markup::new!(@select).to_string(),
+        concat!(
+            r#"<select name="country" id="country" required="">"#,
+            r#"<option value="" disabled selected>Choose…</option>"#,
+            r#"<option value="de">Germany</option><option value="fr">France</option>"#,
+            r#"<option value="it">Italy</option></select>"#
+        )
+    );
+
+    let current = String::from(r#"a "b" & c"#);
+    let select = markup::select("q")
+        .placeholder("Pick one")
+        .options([(r#"a "b" & c"#, "<Quoted>")])
+        .optgroup("Other", vec![(String::from("x"), String::from("X"))])
+        .selected(Some(current.as_str()));
+    assert_eq!(
+        markup::new!(@select).to_string(),
+        concat!(
+            r#"<select name="q"><option value="" disabled>Pick one</option>"#,
+            r#"<option value="a &quot;b&quot; &amp; c" selected>&lt;Quoted&gt;</option>"#,
+            r#"<optgroup label="Other"><option value="x">X</option></optgroup></select>"#
+        )
+    );
+}
+
+markup::define! {
+    Email<'a>(name: &'a str, items: &'a [&'a str]) {
+        @markup::doctype()
+        html {
+            head {
+                style { "p { color: red; }" }
+                title { "Your order" }
+            }
+            body {
+                h1 { "Hello, " @name "!" }
+                p {
+                    "Thanks for your order.\n    It contains:"
+                }
+                ul {
+                    @for item in *items {
+                        li { @item }
+                    }
+                }
+                p {
+                    "Track it "
+                    a[href = "https://example.com/track?id=1&ref=mail"] { "here" }
+                    " or visit "
+                    a[href = "https://example.com"] { "https://example.com" }
+                    "." br; "Bye"
+                }
+                script { "alert('<p>')" }
+                div { "Tom &amp; Jerry" }
+            }
+        }
+    }
+}
+
+#[test]
+fn plain_text() {
+    let email = Email {
+        name: "<Alice & Bob>",
+        items: &["Tea", "Biscuits \"Deluxe\""],
+    };
+    assert_eq!(
+        markup::to_plain_text(&email).unwrap(),
+        concat!(
+            "Hello, <Alice & Bob>!\n\n",
+            "Thanks for your order. It contains:\n\n",
+            "- Tea\n",
+            "- Biscuits \"Deluxe\"\n",
+            "Track it here (https://example.com/track?id=1&ref=mail) or visit https://example.com.\n",
+            "Bye\n\n",
+            "Tom &amp; Jerry"
+        )
+    );
+
+    let html = email.to_string();
+    for split in [1, 2, 3, 7] {
+        let mut writer = markup::PlainTextWriter::new(String::new());
+        for chunk in html.as_bytes().chunks(split) {
+            std::fmt::Write::write_str(&mut writer, std::str::from_utf8(chunk).unwrap()).unwrap();
+        }
+        assert_eq!(
+            writer.finish().unwrap(),
+            markup::to_plain_text(&email).unwrap()
+        );
+    }
+}
+
+#[test]
+fn entity() {
+    use markup::entity::{self, char_ref, UnknownEntity};
+
+    assert_eq!(
+        markup::new! {
+            p { "Wait" @entity::hellip() " 1" @entity::ndash() "2" @entity::nbsp() "km" }
+            span[title = entity::copy()] { @entity::entity("rarr") }
+        }
+        .to_string(),
+        r#"<p>Wait&hellip; 1&ndash;2&nbsp;km</p><span title="&copy;">&rarr;</span>"#
+    );
+    assert_eq!(
+        markup::new!(@char_ref('😀') @char_ref('<')).to_string(),
+        "&#x1F600;&#x3C;"
+    );
+    assert_eq!(entity::lookup("mdash"), Some('—'));
+    assert_eq!(entity::lookup("MDASH"), None);
+
+    let mut string = String::new();
+    let error = markup::Render::render(&entity::entity("nope"), &mut string).unwrap_err();
+    let source = std::error::Error::source(&error).unwrap();
+    assert_eq!(
+        source.downcast_ref::<UnknownEntity>(),
+        Some(&UnknownEntity("nope".into()))
+    );
+    assert!(string.is_empty());
+}
+
+markup::define! {
+    Paragraphs(count: usize, fail_at: Option<usize>) {
+        @for i in 0..*count {
+            @if Some(i) == *fail_at {
+                @Err::<&str, _>(RowError(i))?
+            }
+            p { "Paragraph " @i " & more" }
+        }
+    }
+}
+
+#[test]
+fn render_reader() {
+    use std::io::{BufRead, BufReader, Read};
+
+    let expected = Paragraphs {
+        count: 2000,
+        fail_at: None,
+    }
+    .to_string();
+    assert!(expected.len() > 4 * 8 * 1024);
+    let reader = markup::RenderReader::new(Paragraphs {
+        count: 2000,
+        fail_at: None,
+    });
+    let mut reader = BufReader::with_capacity(7, reader);
+    let mut bytes = Vec::new();
+    loop {
+        let buf = reader.fill_buf().unwrap();
+        if buf.is_empty() {
+            break;
+        }
+        let len = buf.len().min(5);
+        bytes.extend_from_slice(&buf[..len]);
+        reader.consume(len);
+    }
+    assert_eq!(bytes, expected.as_bytes());
+
+    let mut reader = markup::RenderReader::new(Paragraphs {
+        count: 2000,
+        fail_at: Some(1500),
+    });
+    let mut bytes = Vec::new();
+    let error = reader.read_to_end(&mut bytes).unwrap_err();
+    assert_eq!(
+        std::str::from_utf8(&bytes).unwrap(),
+        &expected[..expected.find("<p>Paragraph 1500 ").unwrap()]
+    );
+    let source = std::error::Error::source(&error).unwrap();
+    assert_eq!(source.downcast_ref::<RowError>().unwrap().0, 1500);
+    assert_eq!(reader.read(&mut [0; 16]).unwrap(), 0);
+}
+
+#[test]
+fn stream() {
+    let expected = Paragraphs {
+        count: 40000,
+        fail_at: None,
+    }
+    .to_string();
+    assert!(expected.len() > 1024 * 1024);
+    let chunks = markup::stream(
+        Paragraphs {
+            count: 40000,
+            fail_at: None,
+        },
+        16 * 1024,
+    )
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+    assert!(chunks[..chunks.len() - 1]
+        .iter()
+        .all(|chunk| chunk.len() == 16 * 1024));
+    assert!(!chunks.last().unwrap().is_empty());
+    assert_eq!(chunks.concat(), expected.as_bytes());
+
+    assert_eq!(markup::stream("", 16).count(), 0);
+    let chunks = markup::stream("Hello, world", 5)
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+    assert_eq!(chunks, [&b"Hello"[..], b", wor", b"ld"]);
+
+    let mut stream = markup::stream(
+        Paragraphs {
+            count: 2000,
+            fail_at: Some(1500),
+        },
+        1024,
+    );
+    let mut bytes = Vec::new();
+    let error = loop {
+        match stream.next().unwrap() {
+            Ok(chunk) => bytes.extend(chunk),
+            Err(error) => break error,
+        }
+    };
+    assert_eq!(
+        std::str::from_utf8(&bytes).unwrap(),
+        &expected[..expected.find("<p>Paragraph 1500 ").unwrap()]
+    );
+    assert_eq!(error.downcast_ref::<RowError>().unwrap().0, 1500);
+    assert!(stream.next().is_none());
+}
+
+#[cfg(not(feature = "dev-reload"))]
+#[test]
+fn dev_reload_disabled() {
+    markup::define! {
+        NotReloaded(title: &'static str) {
+            h1 { @title }
+        }
+    }
+
+    let dir = std::env::temp_dir().join(format!("markup-no-dev-reload-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("NotReloaded.markup"), r#"p { "overridden" }"#).unwrap();
+    std::env::set_var("MARKUP_OVERRIDE_DIR", &dir);
+    assert_eq!(NotReloaded { title: "Hi" }.to_string(), "<h1>Hi</h1>");
+}
+
+markup::define_from_file!("tests/templates/article.markup");
+
+#[test]
+fn define_from_file() {
+    markup::define! {
+        InlineArticle<'a>(title: &'a str, tags: &'a [&'a str], body: Option<&'a str>) {
+            article #main.post["data-count" = tags.len()] {
+                h1 { @title }
+                @if let Some(body) = body {
+                    p { @body }
+                } else {
+                    p.empty { "Nothing here & nobody home." }
+                }
+                ul {
+                    @for tag in *tags {
+                        li { @tag }
+                    }
+                }
+            }
+        }
+
+        InlineFooter {
+            footer { "© 2023" }
+        }
+    }
+
+    let tags = ["rust", "<html>"];
+    for body in [Some("Hello, <world>!"), None] {
+        assert_eq!(
+            FileArticle {
+                title: "Title",
+                tags: &tags,
+                body
+            }
+            .to_string(),
+            InlineArticle {
+                title: "Title",
+                tags: &tags,
+                body
+            }
+            .to_string()
+        );
+    }
+    assert_eq!(FileFooter {}.to_string(), InlineFooter {}.to_string());
+    assert_eq!(FileFooter {}.to_string(), "<footer>© 2023</footer>");
+}
+
+struct Row<'a, T> {
+    name: &'a str,
+    values: Vec<T>,
+}
+
+impl<T> Row<'_, T> {
+    fn total(&self) -> T
+    where
+        T: Copy + std::iter::Sum,
+    {
+        self.values.iter().copied().sum()
+    }
+}
+
+markup::render_impl! {
+    for<'a, T> Row<'a, T> where T: markup::Render + Copy + std::iter::Sum {
+        tr {
+            th { @self.name }
+            @for value in &self.values {
+                td { @value }
+            }
+            td.total { @self.total() }
+        }
+    }
+}
+
+#[test]
+fn render_impl() {
+    let row = Row {
+        name: "<b>",
+        values: vec![1, 2, 3],
+    };
+    assert_eq!(
+        markup::new!(table { @row }).to_string(),
+        r#"<table><tr><th>&lt;b&gt;</th><td>1</td><td>2</td><td>3</td><td class="total">6</td></tr></table>"#
+    );
+}
+
+#[test]
+fn deadline() {
+    use markup::{DeadlineWriter, RenderDeadlineExceeded};
+    use std::time::Duration;
+
+    let slow = markup::new(|writer| {
+        for _ in 0..100 {
+            std::thread::sleep(Duration::from_millis(5));
+            writer.write_str("x")?;
+        }
+        Ok(())
+    });
+
+    let mut string = String::new();
+    let error =
+        markup::render_with_deadline(&slow, &mut string, Duration::from_millis(50)).unwrap_err();
+    let exceeded = std::error::Error::source(&error)
+        .unwrap()
+        .downcast_ref::<RenderDeadlineExceeded>()
+        .unwrap();
+    assert_eq!(exceeded.budget, Duration::from_millis(50));
+    assert!(exceeded.elapsed > exceeded.budget);
+    // The default interval only reads the clock every 64 writes.
+    assert_eq!(string.len(), 63);
+
+    let mut writer = DeadlineWriter::new(String::new(), Duration::from_millis(20)).check_every(1);
+    assert!(markup::Render::render(&slow, &mut writer).is_err());
+    assert!(writer.exceeded().is_some());
+    assert!(writer.into_inner().len() < 10);
+
+    let fast = markup::new!(@for i in 0..1000 { p { @i } });
+    let mut string = String::new();
+    markup::render_with_deadline(&fast, &mut string, Duration::from_secs(10)).unwrap();
+    assert_eq!(string, fast.to_string());
+}
+
+#[test]
+fn sorted_attributes() {
+    use std::collections::HashMap;
+
+    let render = |data: &HashMap<String, i32>| {
+        let data = markup::sorted(data.iter().map(|(k, v)| (("data-", k), v)));
+        markup::new!(div[..&data, id = "x", ..[("class", "y")]] {}).to_string()
+    };
+    let names = ["zeta", "alpha", "mu", "beta", "omega", "gamma", "delta"];
+    let first: HashMap<_, _> = names.iter().map(|name| (name.to_string(), 1)).collect();
+    let mut second = HashMap::with_capacity(100);
+    second.extend(names.iter().rev().map(|name| (name.to_string(), 1)));
+    assert_eq!(
+        render(&first),
+        concat!(
+            r#"<div id="x" data-alpha="1" data-beta="1" data-delta="1" data-gamma="1" "#,
+            r#"data-mu="1" data-omega="1" data-zeta="1" class="y"></div>"#
+        )
+    );
+    assert_eq!(render(&first), render(&second));
+
+    // Without sorting, spreads keep the order of their iterator.
+    let attrs = [("b", "1"), ("a", "2")];
+    assert_eq!(
+        markup::new!(p[..attrs, title = "t"] {}).to_string(),
+        r#"<p title="t" b="1" a="2"></p>"#
+    );
+}
+
+// Completes after being polled `0` times, waking itself in between.
+struct Polls<T>(usize, Option<T>);
+
+impl<T: Unpin> std::future::Future for Polls<T> {
+    type Output = T;
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        context: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<T> {
+        if self.0 == 0 {
+            return std::task::Poll::Ready(self.1.take().unwrap());
+        }
+        self.0 -= 1;
+        context.waker().wake_by_ref();
+        std::task::Poll::Pending
+    }
+}
+
+fn failing() -> markup::DynRender<'static> {
+    markup::new(|_| Err(markup::RenderError::wrap(LookupError(7))))
+}
+
+#[test]
+fn suspense() {
+    use markup::suspense::{chunks, SCRIPT};
+
+    markup::define! {
+        Widget(name: &'static str) {
+            div.widget { @name }
+        }
+    }
+
+    let page = || {
+        markup::new! {
+            main {
+                @markup::suspense(markup::new!(p { "Loading slow" }), Polls(5, Some(Widget { name: "slow" })))
+                @markup::suspense("Loading fast", Polls(1, Some(Widget { name: "fast" })))
+                @markup::suspense("Loading failing", async { failing() })
+                    .on_error(markup::new!(p.error { "Unavailable" }))
+            }
+        }
+    };
+
+    let chunks = chunks(page()).collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(
+        chunks,
+        [
+            concat!(
+                r#"<main><template data-markup-slot="0"></template><p>Loading slow</p><template data-markup-slot-end="0"></template>"#,
+                r#"<template data-markup-slot="1"></template>Loading fast<template data-markup-slot-end="1"></template>"#,
+                r#"<template data-markup-slot="2"></template>Loading failing<template data-markup-slot-end="2"></template></main>"#,
+            )
+            .to_string(),
+            format!(
+                r#"{}<template data-markup-fill="2"><p class="error">Unavailable</p></template><script>markupFill(2)</script>"#,
+                SCRIPT
+            ),
+            r#"<template data-markup-fill="1"><div class="widget">fast</div></template><script>markupFill(1)</script>"#.to_string(),
+            r#"<template data-markup-fill="0"><div class="widget">slow</div></template><script>markupFill(0)</script>"#.to_string(),
+        ]
+    );
+
+    assert_eq!(
+        page().to_string(),
+        r#"<main><div class="widget">slow</div><div class="widget">fast</div><p class="error">Unavailable</p></main>"#
+    );
+}
+
+#[test]
+fn suspense_error() {
+    let page = markup::new! {
+        @markup::suspense("a", async { failing() })
+        @markup::suspense("b", std::future::ready("done"))
+    };
+    let mut chunks = markup::suspense::chunks(page).without_script();
+    assert_eq!(
+        chunks.next().unwrap().unwrap(),
+        concat!(
+            r#"<template data-markup-slot="0"></template>a<template data-markup-slot-end="0"></template>"#,
+            r#"<template data-markup-slot="1"></template>b<template data-markup-slot-end="1"></template>"#,
+        )
+    );
+    let error = chunks.next().unwrap().unwrap_err();
+    let source = std::error::Error::source(&error).unwrap();
+    assert_eq!(source.downcast_ref::<LookupError>(), Some(&LookupError(7)));
+    assert_eq!(
+        chunks.next().unwrap().unwrap(),
+        r#"<template data-markup-fill="1">done</template><script>markupFill(1)</script>"#
+    );
+    assert!(chunks.next().is_none());
+}
+
+#[test]
+fn static_escaping() {
+    let runtime = String::from("é<ü>&\"'ß\u{1F600}&amp;<");
+    assert_eq!(
+        markup::new!(p[title = "é<ü>&\"'ß\u{1F600}&amp;<"] { "é<ü>&\"'ß\u{1F600}&amp;<" })
+            .to_string(),
+        markup::new!(p[title = runtime.as_str()] { @runtime }).to_string()
+    );
+}
+
+#[test]
+fn literal_folding() {
+    use std::convert::identity as id;
+
+    assert_eq!(
+        markup::new! {
+            42 " " -7 " " 0x1F " " -128i8 " " 80u8 " " 1.5 " " -0.0 " " 1e3 " " 2.5f32 " " 0.1f32
+            " " 3f64 " " true " " false " " '<' " " "x & y"
+        }
+        .to_string(),
+        markup::new! {
+            @id(42) " " @id(-7) " " @id(0x1F) " " @id(-128i8) " " @id(80u8) " " @id(1.5)
+            " " @id(-0.0) " " @id(1e3) " " @id(2.5f32) " " @id(0.1f32) " " @id(3f64) " "
+            @id(true) " " @id(false) " " @id('<') " " @id("x & y")
+        }
+        .to_string()
+    );
+    assert_eq!(
+        markup::new!(input[maxlength = 80u8, min = -5, step = 0.5, checked = true, hidden = false, value = 'x'];)
+            .to_string(),
+        markup::new!(input[maxlength = id(80u8), min = id(-5), step = id(0.5), checked = id(true), hidden = id(false), value = id('x')];)
+            .to_string()
+    );
+}
+
+#[test]
+fn attribute_forwarding() {
+    markup::define! {
+        Input<'a>(label: &'a str, name: &'a str, ..rest) {
+            label {
+                @label
+                input[type = "text", name = name, ..rest];
+            }
+        }
+        Form {
+            form {
+                @Input { label: "Name", name: "name", placeholder = "<Jane & John>", "data-test" = "y" }
+                @Input { label: "Age", name: "age", required = true, hidden = false, type = "number" }
+            }
+        }
+    }
+
+    assert_eq!(
+        Form {}.to_string(),
+        concat!(
+            r#"<form><label>Name<input type="text" name="name" placeholder="&lt;Jane &amp; John&gt;" data-test="y"></label>"#,
+            r#"<label>Age<input type="text" name="age" required type="number"></label></form>"#,
+        )
+    );
+
+    let mut rest = markup::AttrMap::new();
+    rest.insert("size", 10).unwrap();
+    let input = Input {
+        label: "Plain",
+        name: "plain",
+        __markup_rest: rest,
+    };
+    assert_eq!(
+        input.to_string(),
+        r#"<label>Plain<input type="text" name="plain" size="10"></label>"#
+    );
+    assert_eq!(input.__markup_rest.len(), 1);
+    assert_eq!(
+        input.__markup_rest.get("size").unwrap().as_str(),
+        Some("10")
+    );
+    assert!(input.__markup_rest.get("label").is_none());
+}
+
+#[test]
+fn href() {
+    use markup::{RenderError, Segment, ToHref};
+
+    enum Route<'a> {
+        Home,
+        User { name: &'a str },
+        Search { query: &'a str, page: u32 },
+    }
+
+    impl ToHref for Route<'_> {
+        fn write_href(&self, writer: &mut impl std::fmt::Write) -> Result<(), RenderError> {
+            match self {
+                Route::Home => "/".write_href(writer),
+                Route::User { name } => {
+                    "/users/".write_href(writer)?;
+                    Segment(name).write_href(writer)
+                }
+                Route::Search { query, page } => {
+                    "/search?q=".write_href(writer)?;
+                    Segment(query).write_href(writer)?;
+                    Ok(write!(writer, "&page={}", page)?)
+                }
+            }
+        }
+    }
+
+    assert_eq!(
+        markup::new! {
+            a[href = markup::href(Route::Home)] { "Home" }
+            a[href = markup::href(Route::User { name: "Tom & Jerry" })] { "Tom & Jerry" }
+            a[href = markup::href(Route::Search { query: "a b/ü\"", page: 2 })] { "Search" }
+            a[href = markup::href("/?a=1&b=\"2\"")] {}
+        }
+        .to_string(),
+        concat!(
+            r#"<a href="/">Home</a>"#,
+            r#"<a href="/users/Tom%20%26%20Jerry">Tom &amp; Jerry</a>"#,
+            r#"<a href="/search?q=a%20b%2F%C3%BC%22&amp;page=2">Search</a>"#,
+            r#"<a href="/?a=1&amp;b=&quot;2&quot;"></a>"#,
+        )
+    );
+}
+
+#[test]
+fn deferred() {
+    use markup::Deferred;
+
+    markup::define! {
+        Layout<'a>(deferred: &'a Deferred, body: markup::DynRender<'a>) {
+            body {
+                @body
+                @deferred.flush()
+            }
+        }
+        Map<'a>(deferred: &'a Deferred, id: u32) {
+            @deferred.push_once("maps.js", markup::new!(script[src = "/maps.js"] {}));
+            @let modal = format!("map-{}", id);
+            @deferred.push(markup::new!(div.modal #{modal} {}));
+            div.map { @id }
+        }
+        Chart<'a>(deferred: &'a Deferred) {
+            @deferred.push_once("charts.js", markup::new!(script[src = "/charts.js"] {}));
+            div.chart {}
+        }
+    }
+
+    let deferred = &Deferred::new();
+    let page = Layout {
+        deferred,
+        body: markup::new! {
+            @Map { deferred, id: 1 }
+            @Chart { deferred }
+            @Map { deferred, id: 2 }
+        },
+    };
+    assert_eq!(
+        page.to_string(),
+        concat!(
+            r#"<body><div class="map">1</div><div class="chart"></div><div class="map">2</div>"#,
+            r#"<script src="/maps.js"></script><div id="map-1" class="modal"></div>"#,
+            r#"<script src="/charts.js"></script><div id="map-2" class="modal"></div></body>"#,
+        )
+    );
+    assert!(deferred.is_empty());
+
+    // Keys stay registered after a flush, other fragments are kept for the next one.
+    deferred.push_once("maps.js", "again");
+    deferred.push("late");
+    assert_eq!(deferred.len(), 1);
+    assert_eq!(markup::new!(@deferred.flush()).to_string(), "late");
+
+    // A failing fragment is dropped, the ones after it are kept for the next flush.
+    deferred.push("a");
+    deferred.push(markup::entity::entity("nope"));
+    deferred.push("b");
+    deferred.push("c");
+    let mut string = String::new();
+    assert!(markup::Render::render(&deferred.flush(), &mut string).is_err());
+    assert_eq!(string, "a");
+    assert_eq!(deferred.len(), 2);
+    assert_eq!(markup::new!(@deferred.flush()).to_string(), "bc");
+
+    let empty = &Deferred::new();
+    assert_eq!(
+        Layout {
+            deferred: empty,
+            body: markup::new!(p { "Nothing" })
+        }
+        .to_string(),
+        "<body><p>Nothing</p></body>"
+    );
+}
+
+#[test]
+fn default_type_parameters() {
+    use markup::RenderAttributeValue;
+
+    markup::define! {
+        Picker<V: RenderAttributeValue = &'static str>(options: Vec<(V, String)>) {
+            select {
+                @for (value, label) in options {
+                    option[value = value] { @label }
+                }
+            }
+        }
+    }
+
+    let picker: Picker = Picker {
+        options: vec![("a", "A".to_string())],
+    };
+    assert_eq!(
+        picker.to_string(),
+        r#"<select><option value="a">A</option></select>"#
+    );
+    assert_eq!(
+        Picker::<u32> {
+            options: vec![(1, "One".to_string())],
+        }
+        .to_string(),
+        r#"<select><option value="1">One</option></select>"#
+    );
+}
+
+// Defaults for const parameters need Rust 1.59.
+#[rustversion::since(1.59)]
+#[test]
+fn default_const_parameters() {
+    markup::define! {
+        Grid<const COLUMNS: usize = 2>(cells: Vec<u32>) {
+            @for row in cells.chunks(COLUMNS) {
+                div.row { @for cell in row { span { @cell } } }
+            }
+        }
+        Form(grid: Grid) {
+            form { @grid }
+        }
+    }
+
+    assert_eq!(
+        Form {
+            grid: Grid {
+                cells: vec![1, 2, 3]
+            },
+        }
+        .to_string(),
+        concat!(
+            "<form>",
+            r#"<div class="row"><span>1</span><span>2</span></div>"#,
+            r#"<div class="row"><span>3</span></div></form>"#,
+        )
+    );
+    assert_eq!(
+        Grid::<3> {
+            cells: vec![1, 2, 3]
+        }
+        .to_string(),
+        r#"<div class="row"><span>1</span><span>2</span><span>3</span></div>"#
+    );
+}
+
+#[test]
+fn buffer() {
+    use std::io::Write;
+
+    let mut buffer = markup::Buffer::with_capacity(64);
+    let (pointer, capacity) = (buffer.as_bytes().as_ptr(), buffer.capacity());
+    assert_eq!(
+        buffer.render(markup::new!(p { "<a>" })).unwrap(),
+        b"<p>&lt;a&gt;</p>"
+    );
+    assert_eq!(buffer.render(markup::new!(br;)).unwrap(), b"<br>");
+    assert_eq!(buffer.as_bytes().as_ptr(), pointer);
+    assert_eq!(buffer.capacity(), capacity);
+
+    // Embedded as is, without escaping.
+    buffer.write_all(b" & more").unwrap();
+    let embedded = &buffer;
+    assert_eq!(
+        markup::new!(div { @embedded }).to_string(),
+        "<div><br> & more</div>"
+    );
+
+    let mut string = String::new();
+    let error = markup::Render::render(&markup::Buffer::from(vec![0xff]), &mut string).unwrap_err();
+    let source = std::error::Error::source(&error).unwrap();
+    assert!(source.is::<std::str::Utf8Error>());
+
+    assert_eq!(buffer.take_string().unwrap(), "<br> & more");
+    assert!(buffer.is_empty());
+    let mut invalid = markup::Buffer::from(vec![b'a', 0xff]);
+    assert!(invalid.take_string().is_err());
+    assert_eq!(invalid.as_bytes(), b"a\xff");
+
+    fn send<T: Send>(_: &T) {}
+    send(&buffer);
+}
+
+#[test]
+fn cfg() {
+    markup::define! {
+        Toolbar(
+            user: &'static str,
+            #[cfg(any())]
+            permissions: Vec<&'static str>,
+        ) {
+            nav {
+                @user
+                @cfg(any()) {
+                    @for permission in permissions { " " @permission }
+                } @cfg_else {
+                    " (no permissions)"
+                }
+                @cfg(debug_assertions) { " [debug]" }
+                @cfg(all()) { br; }
+            }
+        }
+    }
+
+    let expected = if cfg!(debug_assertions) {
+        "<nav>alice (no permissions) [debug]<br></nav>"
+    } else {
+        "<nav>alice (no permissions)<br></nav>"
+    };
+    assert_eq!(Toolbar { user: "alice" }.to_string(), expected);
+    assert_eq!(
+        markup::new!(@if true { @cfg(all()) { "a" } }).to_string(),
+        "a"
+    );
+}
+
+#[test]
+fn render_to_string() {
+    use markup::Render;
+
+    markup::define! {
+        Item<'a>(name: &'a str) {
+            li { @name }
+        }
+        List<'a>(names: &'a [&'a str]) {
+            ul { @for name in *names { @Item { name } } }
+        }
+    }
+
+    let list = List {
+        names: &["<a>", "b & c"],
+    };
+    let expected = "<ul><li>&lt;a&gt;</li><li>b &amp; c</li></ul>";
+    assert_eq!(list.render_to_string().unwrap(), expected);
+    assert_eq!(list.render_to_vec().unwrap(), expected.as_bytes());
+    // Only the static output counts, not the rendered items.
+    assert_eq!(list.size_hint(), "<ul></ul>".len());
+    assert_eq!(Item { name: "" }.size_hint(), "<li></li>".len());
+    assert_eq!(Render::size_hint(&&list), list.size_hint());
+
+    let dynamic = markup::new!(p { @List { names: &["x"] } });
+    assert_eq!(
+        dynamic.render_to_string().unwrap(),
+        "<p><ul><li>x</li></ul></p>"
+    );
+    assert_eq!(dynamic.size_hint(), 0);
+
+    let error = markup::new!(@markup::entity::entity("nope")).render_to_vec();
+    assert!(error.is_err());
+}
+
+#[test]
+fn collections() {
+    markup::define! {
+        Card(title: String) {
+            div.card { @title }
+        }
+        Cards(cards: Vec<Card>) {
+            section { @cards }
+        }
+        Names<I: markup::Render>(names: I) {
+            ul { @names }
+        }
+    }
+
+    let cards = Cards {
+        cards: vec![
+            Card {
+                title: "<a>".into(),
+            },
+            Card { title: "b".into() },
+        ],
+    };
+    assert_eq!(
+        cards.to_string(),
+        r#"<section><div class="card">&lt;a&gt;</div><div class="card">b</div></section>"#
+    );
+    assert_eq!(Cards { cards: vec![] }.to_string(), "<section></section>");
+
+    let names = ["x", "y"];
+    assert_eq!(markup::new!(@names " " @names[..]).to_string(), "xy xy");
+    let items = Names {
+        names: markup::from_iter(names.iter().map(|name| markup::new!(li { @name }))),
+    };
+    assert_eq!(items.to_string(), "<ul><li>x</li><li>y</li></ul>");
+    // Each render iterates a fresh clone, and the template can still be shared between threads.
+    assert_eq!(items.to_string(), "<ul><li>x</li><li>y</li></ul>");
+    fn assert_sync(_: &impl Sync) {}
+    assert_sync(&items);
+
+    // Elements after the first failing one aren't rendered.
+    let mut string = String::new();
+    let entities = [
+        markup::entity::entity("amp"),
+        markup::entity::entity("nope"),
+        markup::entity::entity("lt"),
+    ];
+    assert!(markup::Render::render(&entities, &mut string).is_err());
+    assert_eq!(string, "&amp;");
+}
+
+fn shared_lookup(id: u32) -> Result<Option<&'static str>, std::sync::Arc<std::io::Error>> {
+    lookup(id).map_err(|error| {
+        std::sync::Arc::new(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            error.to_string(),
+        ))
+    })
+}
+
+#[test]
+fn result() {
+    use markup::Render;
+
+    assert_eq!(
+        markup::new!(p[title = shared_lookup(2)] { @shared_lookup(1) }).to_string(),
+        "<p>&lt;one&gt;</p>"
+    );
+
+    let error = markup::new!(p { @shared_lookup(3) " after" })
+        .render_to_string()
+        .unwrap_err();
+    assert!(matches!(error, markup::RenderError::Other(_)));
+    let shared = error
+        .downcast_ref::<std::sync::Arc<std::io::Error>>()
+        .unwrap();
+    assert_eq!(shared.kind(), std::io::ErrorKind::NotFound);
+    assert_eq!(shared.to_string(), "lookup of 3 failed");
+
+    let mut string = String::new();
+    let error = markup::new!(p[title = shared_lookup(4)] { "body" })
+        .render(&mut string)
+        .unwrap_err();
+    assert_eq!(error.to_string(), "lookup of 4 failed");
+    assert_eq!(string, r#"<p title=""#);
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MyError(String);
+
+impl std::fmt::Display for MyError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(fmt, "can't render {:?}", self.0)
+    }
+}
+
+impl std::error::Error for MyError {}
+
+fn markdown_to_html(body: &str) -> Result<String, MyError> {
+    match body.strip_prefix("# ") {
+        Some(title) => Ok(format!("<h1>{}</h1>", title)),
+        None => Err(MyError(body.to_string())),
+    }
+}
+
+#[test]
+fn result_with_try() {
+    markup::define! {
+        Post(body: String) {
+            article { @markdown_to_html(body)? }
+        }
+    }
+
+    let post = Post {
+        body: "# Hello".to_string(),
+    };
+    assert_eq!(
+        post.to_string(),
+        "<article>&lt;h1&gt;Hello&lt;/h1&gt;</article>"
+    );
+
+    let post = Post {
+        body: "Hello".to_string(),
+    };
+    let error = markup::Render::render(&post, &mut String::new()).unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<MyError>(),
+        Some(&MyError("Hello".to_string()))
+    );
+    assert_eq!(error.to_string(), r#"can't render "Hello""#);
+}
+
+#[test]
+fn render_buffered() {
+    use markup::Render;
+    use std::io::Write;
+
+    #[derive(Default)]
+    struct Counting {
+        bytes: Vec<u8>,
+        writes: usize,
+        flushes: usize,
+    }
+
+    impl std::io::Write for Counting {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.writes += 1;
+            self.bytes.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+
+    markup::define! {
+        Page(rows: usize) {
+            table {
+                @for row in 0..*rows {
+                    tr { td { @row } td { "<" @row ">" } }
+                }
+            }
+        }
+    }
+
+    let page = Page { rows: 100 };
+    let mut direct = Counting::default();
+    write!(direct, "{}", page).unwrap();
+    let mut buffered = Counting::default();
+    page.render_buffered(&mut buffered).unwrap();
+    assert_eq!(buffered.bytes, direct.bytes);
+    assert!(direct.writes > 500, "{}", direct.writes);
+    assert_eq!(buffered.writes, 1);
+    assert_eq!(buffered.flushes, 1);
+
+    // Writes larger than a chunk are passed through.
+    let large = "a".repeat(20_000);
+    let mut buffered = Counting::default();
+    markup::new!(p { @large })
+        .render_buffered(&mut buffered)
+        .unwrap();
+    assert_eq!(buffered.writes, 3);
+    assert_eq!(buffered.bytes.len(), 20_007);
+
+    let error = page.render_buffered(&mut &mut [0u8; 16][..]).unwrap_err();
+    let source = std::error::Error::source(&error).unwrap();
+    assert_eq!(
+        source.downcast_ref::<std::io::Error>().unwrap().kind(),
+        std::io::ErrorKind::WriteZero
+    );
+}
+
+#[test]
+fn attribute_escaping() {
+    use std::fmt::Write;
+
+    let value = r#"a'"&b"#;
+    let class = "it's";
+    assert_eq!(
+        markup::new!(p #{value} .{class} [title = value, "data-x" = "it's", alt = markup::raw("x'y")] { @value "'" })
+            .to_string(),
+        concat!(
+            r#"<p id="a&#39;&quot;&amp;b" class="it&#39;s" title="a&#39;&quot;&amp;b" "#,
+            r#"data-x="it&#39;s" alt="x&#39;y">a'&quot;&amp;b'</p>"#,
+        )
+    );
+
+    let mut string = String::new();
+    markup::escape_attribute(value, &mut string).unwrap();
+    write!(markup::EscapeAttribute(&mut string), " {}", class).unwrap();
+    assert_eq!(string, "a&#39;&quot;&amp;b it&#39;s");
+}
+
+t! {
+    t_legacy_escaping,
+    {
+        #[markup(legacy_escaping)]
+        Legacy(value: &'static str) {
+            p[title = value, alt = "it's"] { @value }
+        }
+        #[markup(legacy_escaping, xml)]
+        LegacyXml(value: &'static str) {
+            p[title = value] {}
+        }
+    },
+    Legacy { value: "it's" } => r#"<p title="it's" alt="it's">it's</p>"#,
+    LegacyXml { value: "it's" } => r#"<p title="it&#39;s"/>"#,
+}
+
+#[test]
+fn try_new() {
+    use markup::Render;
+
+    let io = markup::try_new(|writer| {
+        let contents = std::fs::read_to_string("/nonexistent/markup.txt")?;
+        writer.write_str(&contents)?;
+        Ok(())
+    });
+    let error = io.render_to_string().unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<std::io::Error>().unwrap().kind(),
+        std::io::ErrorKind::NotFound
+    );
+    assert!(error.downcast_ref::<LookupError>().is_none());
+
+    let custom = markup::try_new(|writer| {
+        for id in 1..4 {
+            markup::new!(p { @lookup(id)? }).render(&mut &mut *writer)?;
+        }
+        Ok(())
+    });
+    let mut string = String::new();
+    let error = custom.render(&mut string).unwrap_err();
+    assert_eq!(string, "<p>&lt;one&gt;</p><p></p><p>");
+    assert_eq!(error.downcast_ref::<LookupError>(), Some(&LookupError(3)));
+
+    let error = markup::try_new(|_| Ok(std::fmt::Write::write_str(&mut FailingWriter, "x")?))
+        .render_to_string()
+        .unwrap_err();
+    assert!(matches!(error, markup::RenderError::Fmt(_)));
+    assert!(error.downcast_ref::<std::fmt::Error>().is_some());
+}
+
+#[test]
+fn render_by_reference() {
+    use std::sync::Arc;
+
+    markup::define! {
+        Homepage(title: String, visits: u32) {
+            h1[title = title, "data-visits" = visits] { @title }
+        }
+        Shared(page: Arc<Homepage>) {
+            main { @page }
+        }
+    }
+
+    let page = Arc::new(Homepage {
+        title: "<Home>".into(),
+        visits: 3,
+    });
+    let expected = r#"<h1 title="&lt;Home&gt;" data-visits="3">&lt;Home&gt;</h1>"#;
+    let threads = (0..4)
+        .map(|_| {
+            let page = Arc::clone(&page);
+            std::thread::spawn(move || page.to_string())
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        assert_eq!(thread.join().unwrap(), expected);
+    }
+    // Rendering doesn't consume the template.
+    assert_eq!(page.to_string(), expected);
+    assert_eq!(
+        Shared { page: page.clone() }.to_string(),
+        format!("<main>{}</main>", expected)
+    );
+
+    let title = String::from("a&b");
+    let name: &&str = &"n";
+    let count = 2u32;
+    let rc = std::rc::Rc::new(Some("x"));
+    assert_eq!(
+        markup::new!(input[title = &title, name = name, size = &count, value = &rc];).to_string(),
+        r#"<input title="a&amp;b" name="n" size="2" value="x">"#
+    );
+}
+
+#[test]
+fn std_types() {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6};
+    use std::num::{NonZeroI128, NonZeroI8, NonZeroU32, NonZeroUsize};
+
+    markup::define! {
+        Node(id: NonZeroU32, ip: IpAddr, socket: SocketAddr) {
+            div[id = id, "data-ip" = ip, "data-socket" = socket] { @id " " @ip " " @socket }
+        }
+    }
+
+    assert_eq!(
+        Node {
+            id: NonZeroU32::new(7).unwrap(),
+            ip: IpAddr::V6(Ipv6Addr::LOCALHOST),
+            socket: "127.0.0.1:8080".parse().unwrap(),
+        }
+        .to_string(),
+        r#"<div id="7" data-ip="::1" data-socket="127.0.0.1:8080">7 ::1 127.0.0.1:8080</div>"#
+    );
+
+    let v6 = SocketAddrV6::new("fe80::1".parse().unwrap(), 443, 0, 2);
+    let socket = SocketAddr::V6(v6);
+    let ip = Ipv4Addr::new(10, 0, 0, 1);
+    assert_eq!(
+        markup::new!(
+            @NonZeroI8::new(-8).unwrap() " " @NonZeroUsize::new(usize::MAX).unwrap() " "
+            @NonZeroI128::new(i128::MIN).unwrap() " " @ip " " @v6 " " @socket
+        )
+        .to_string(),
+        format!(
+            "-8 {} {} 10.0.0.1 [fe80::1%2]:443 [fe80::1%2]:443",
+            usize::MAX,
+            i128::MIN
+        )
+    );
+}
+
+#[test]
+fn display() {
+    use std::fmt::Write;
+
+    let html = markup::display(markup::new!(p { "<a> & b" }));
+    assert_eq!(format!("{}", html), "<p>&lt;a&gt; &amp; b</p>");
+    assert_eq!(format!("[{:>3}]", markup::display(1u8)), "[1]");
+    assert!(html.take_error().is_none());
+
+    let failing = markup::display(markup::try_new(|writer| {
+        writer.write_str("partial")?;
+        Err(std::io::Error::from(std::io::ErrorKind::BrokenPipe).into())
+    }));
+    let mut string = String::new();
+    assert_eq!(write!(string, "{}", failing), Err(std::fmt::Error));
+    assert_eq!(string, "partial");
+    let error = failing.take_error().unwrap();
+    assert_eq!(
+        error.downcast_ref::<std::io::Error>().unwrap().kind(),
+        std::io::ErrorKind::BrokenPipe
+    );
+    assert!(failing.take_error().is_none());
+}
+
+#[test]
+fn trusted() {
+    use markup::Trusted;
+
+    const ICON: Trusted = Trusted::from_static(r#"<i class="icon"></i>"#);
+    let html = ICON + &Trusted::from_static("&amp;");
+    assert_eq!(html.as_str(), r#"<i class="icon"></i>&amp;"#);
+    let template = markup::new! {
+        div["data-icon" = &html] { @html "<b>" }
+    };
+    assert_eq!(
+        template.to_string(),
+        r#"<div data-icon="<i class="icon"></i>&amp;"><i class="icon"></i>&amp;&lt;b&gt;</div>"#
+    );
+
+    let mut html = Trusted::assert_trusted(String::from("<hr>"));
+    html += &ICON;
+    assert_eq!(
+        markup::new!(@html).to_string(),
+        r#"<hr><i class="icon"></i>"#
+    );
+    assert_eq!(Trusted::default().as_str(), "");
+}
+
+markup::define! {
+    #[markup(trim)]
+    TrimmedArticle(title: &'static str) {
+        article {
+            h1 { @title }
+            p {
+                "
+                Lorem ipsum
+                dolor sit amet,
+                "
+                em { " consectetur " }
+                "
+                adipiscing elit.
+                "
+            }
+            pre {
+                "  fn main() {\n      println!();\n  }\n"
+            }
+        }
+    }
+
+    UntrimmedArticle(title: &'static str) {
+        article {
+            h1 { @title }
+            p {
+                "
+                Lorem ipsum
+                dolor sit amet,
+                "
+                em { " consectetur " }
+                "
+                adipiscing elit.
+                "
+            }
+            pre {
+                "  fn main() {\n      println!();\n  }\n"
+            }
+        }
+    }
+}
+
+#[test]
+fn trim() {
+    let trimmed = TrimmedArticle { title: "  A  B  " }.to_string();
+    let untrimmed = UntrimmedArticle { title: "  A  B  " }.to_string();
+    assert_eq!(
+        trimmed,
+        "<article><h1>  A  B  </h1><p>Lorem ipsum dolor sit amet,<em> consectetur </em>adipiscing elit.</p>\
+         <pre>  fn main() {\n      println!();\n  }\n</pre></article>"
+    );
+    assert!(trimmed.len() < untrimmed.len());
+    let pre = |html: &str| html[html.find("<pre>").unwrap()..].to_string();
+    assert_eq!(pre(&trimmed), pre(&untrimmed));
+
+    markup::define! {
+        #[markup(trim)]
+        Inline(name: &'static str) {
+            "Hello,  " @name "\n  and " b { "you" } "\n"
+            @for i in 1..3 {
+                " " @i
+            }
+        }
+    }
+    assert_eq!(
+        Inline { name: "Ferris" }.to_string(),
+        "Hello, Ferris and <b>you</b> 1 2"
+    );
+}
+
+pub enum Shape {
+    Circle { radius: u32 },
+    Rect(u32, u32),
+    Point,
+}
+
+markup::define! {
+    ShapeLabel(shape: Shape) {
+        @match shape {
+            Shape::Circle { radius } => { "circle r=" @radius }
+            Shape::Rect(width, height) if width == height => { "square " @width }
+            Shape::Rect(width, height) => { "rect " @width "x" @height }
+            _ => { em { "point" } }
+        }
+    }
+}
+
+#[test]
+fn match_enum() {
+    let label = |shape| ShapeLabel { shape }.to_string();
+    assert_eq!(label(Shape::Circle { radius: 3 }), "circle r=3");
+    assert_eq!(label(Shape::Rect(2, 2)), "square 2");
+    assert_eq!(label(Shape::Rect(2, 5)), "rect 2x5");
+    assert_eq!(label(Shape::Point), "<em>point</em>");
+}
+
+markup::define! {
+    SearchPage<'a, T: markup::Render>(query: &'a str, results: &'a [T]) {
+        h1 { "Search" }
+        input[name = "q", value = query];
+        @fragment results {
+            div #results {
+                @for result in *results {
+                    p { @result }
+                }
+                @fragment count {
+                    small { @results.len() " results for " @query }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn fragment() {
+    let page = SearchPage {
+        query: "a&b",
+        results: &[1, 2],
+    };
+    let html = page.to_string();
+    let mut results = String::new();
+    page.render_results(&mut results).unwrap();
+    assert_eq!(
+        results,
+        r#"<div id="results"><p>1</p><p>2</p><small>2 results for a&amp;b</small></div>"#
+    );
+    assert_eq!(
+        html,
+        format!(
+            r#"<h1>Search</h1><input name="q" value="a&amp;b">{}"#,
+            results
+        )
+    );
+    let mut count = String::new();
+    page.render_count(&mut count).unwrap();
+    assert_eq!(count, "<small>2 results for a&amp;b</small>");
+}
+
+#[test]
+fn rendered_len() {
+    use markup::Render;
+
+    markup::define! {
+        Counted<'a>(name: &'a str, numbers: &'a [i64]) {
+            h1 { "Tom & Jerry's " @name }
+            ul {
+                @for number in numbers.iter() {
+                    li { @number " " @{ *number as f64 / 3.0 } }
+                }
+            }
+            @Paragraphs { count: 3, fail_at: None }
+        }
+    }
+    let template = Counted {
+        name: "<Ferris> — 🦀",
+        numbers: &[0, -7, 1234567890, i64::MIN],
+    };
+    assert_eq!(
+        template.rendered_len().unwrap(),
+        template.render_to_vec().unwrap().len() as u64
+    );
+    assert_eq!(markup::new!().rendered_len().unwrap(), 0);
+
+    let mut html = String::new();
+    let mut writer = markup::tee(&mut html, markup::CountingWriter::new());
+    template.render(&mut writer).unwrap();
+    let (_, counter) = writer.into_inner();
+    assert_eq!(html, template.to_string());
+    assert_eq!(counter.count(), html.len() as u64);
+
+    let mut bytes = Vec::new();
+    let mut writer = markup::tee(markup::CountingWriter::new(), &mut bytes);
+    std::io::Write::write_all(&mut writer, b"abc").unwrap();
+    assert_eq!(writer.into_inner().0.count(), 3);
+    assert_eq!(bytes, b"abc");
+
+    let mut writer = markup::tee(markup::CountingWriter::new(), FailingWriter);
+    assert!(template.render(&mut writer).is_err());
+    // Only the first write, of the static start of the template, was counted.
+    assert_eq!(
+        writer.into_inner().0.count(),
+        "<h1>Tom &amp; Jerry's ".len() as u64
+    );
+    let mut html = String::new();
+    let mut writer = markup::tee(FailingWriter, &mut html);
+    assert!(template.render(&mut writer).is_err());
+    assert_eq!(html, "");
+}
+
+#[test]
+fn spread_collections() {
+    let data = std::collections::BTreeMap::from([
+        (
+            String::from("hx-get"),
+            Some(String::from("/items?page=2&sort=\"name\"")),
+        ),
+        (String::from("hx-swap"), None),
+        (String::from("hx-target"), Some(String::from("#items"))),
+    ]);
+    let flags = vec![("hidden", true), ("disabled", false), ("inert", true)];
+    let template = markup::new! {
+        div[id = "list", ..&data, ..flags.iter().copied()] {}
+    };
+    assert_eq!(
+        template.to_string(),
+        r##"<div id="list" hx-get="/items?page=2&amp;sort=&quot;name&quot;" hx-target="#items" hidden inert></div>"##
+    );
+
+    // Literal and spread attributes with the same name are both written.
+    let extra = vec![("class", "b")];
+    let template = markup::new!(p.a[..&extra] {});
+    assert_eq!(template.to_string(), r#"<p class="a" class="b"></p>"#);
+}
+
+#[test]
+fn spread_invalid_names() {
+    let hostile = vec![("x onclick=alert(1) y", "v")];
+    let template = markup::new!(div[id = "a", ..&hostile] {});
+    let mut html = String::new();
+    let error = markup::Render::render(&template, &mut html).unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<markup::InvalidAttributeName>(),
+        Some(&markup::InvalidAttributeName(String::from(
+            "x onclick=alert(1) y"
+        )))
+    );
+    assert_eq!(html, r#"<div id="a" "#);
+
+    for name in ["", "a\"b", "a'b", "a>b", "a/b", "a=b", "a\tb", "a\u{0}b"] {
+        let attributes = vec![(name, true)];
+        let template = markup::new!(div[..&attributes] {});
+        let error = markup::Render::render(&template, &mut String::new()).unwrap_err();
+        assert!(error
+            .downcast_ref::<markup::InvalidAttributeName>()
+            .is_some());
+    }
+
+    let valid = vec![(("data-", "x"), "1"), (("aria-", "label"), "2")];
+    let template = markup::new!(div[..&valid] {});
+    assert_eq!(
+        template.to_string(),
+        r#"<div data-x="1" aria-label="2"></div>"#
+    );
+}
+
+#[test]
+fn lazy() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn highlight(code: &str) -> String {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        format!("<{}>", code)
+    }
+
+    markup::define! {
+        Snippet<'a>(code: &'a str, show: bool, title: Option<&'a str>) {
+            @let highlighted = markup::lazy(|| highlight(code));
+            @if *show {
+                pre { @highlighted @highlighted }
+            }
+            @if let Some(title) = title {
+                h2[title = markup::lazy(|| highlight(title))] {}
+            }
+        }
+    }
+
+    let hidden = Snippet {
+        code: "a & b",
+        show: false,
+        title: None,
+    };
+    assert_eq!(hidden.to_string(), "");
+    assert_eq!(CALLS.load(Ordering::SeqCst), 0);
+
+    let shown = Snippet {
+        code: "a & b",
+        show: true,
+        title: Some("x"),
+    };
+    assert_eq!(
+        shown.to_string(),
+        r#"<pre>&lt;a &amp; b&gt;&lt;a &amp; b&gt;</pre><h2 title="&lt;x&gt;"></h2>"#
+    );
+    assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+
+    let none = markup::lazy(|| None::<&str>);
+    assert_eq!(markup::new!(p[title = &none] {}).to_string(), "<p></p>");
+}
+
+markup::define! {
+    HtmlEntry<'a>(title: &'a str, draft: bool) {
+        entry[draft = draft, hidden = true, lang = None::<&str>] {
+            title { @title }
+            link[href = "/a?b=1&c='2'"];
+            summary {}
+        }
+    }
+
+    #[markup(xml)]
+    XmlEntry<'a>(title: &'a str, draft: bool) {
+        entry[draft = draft, hidden = true, lang = None::<&str>] {
+            title { @title }
+            link[href = "/a?b=1&c='2'"];
+            summary {}
+        }
+    }
+
+    #[markup(xml)]
+    Sprite(paths: &'static [&'static str]) {
+        @markup::xml_declaration("1.0", "UTF-8")
+        svg[xmlns = "http://www.w3.org/2000/svg", focusable = false] {
+            @for d in paths.iter() {
+                path[d = d];
+            }
+        }
+    }
+}
+
+#[test]
+fn xml() {
+    assert_eq!(
+        HtmlEntry {
+            title: "Tom's",
+            draft: false
+        }
+        .to_string(),
+        r#"<entry hidden><title>Tom's</title><link href="/a?b=1&amp;c=&#39;2&#39;"><summary></summary></entry>"#
+    );
+    assert_eq!(
+        XmlEntry {
+            title: "Tom's",
+            draft: false
+        }
+        .to_string(),
+        r#"<entry draft="false" hidden="true"><title>Tom's</title><link href="/a?b=1&amp;c=&#39;2&#39;"/><summary/></entry>"#
+    );
+    assert_eq!(
+        Sprite {
+            paths: &["M0 0h10v10z", "M5 5l1 1"]
+        }
+        .to_string(),
+        r#"<?xml version="1.0" encoding="UTF-8"?><svg xmlns="http://www.w3.org/2000/svg" focusable="false"><path d="M0 0h10v10z"/><path d="M5 5l1 1"/></svg>"#
+    );
+}
+
+#[test]
+fn pretty() {
+    use markup::Render;
+
+    let template = markup::new! {
+        @markup::doctype()
+        html {
+            body {
+                h1 { "Title" }
+                p { "Hello, " b { "world" } "! a < b" }
+                br;
+                input[value = "a > b"];
+                div {}
+                pre { "  fn main() {\n      println!();\n  }\n" }
+                @markup::raw("<svg><path d=\"M0 0\" data-x='a>b'/></svg>")
+            }
+        }
+    };
+    assert_eq!(
+        markup::pretty(&template, 2).render_to_string().unwrap(),
+        r#"<!DOCTYPE html>
+<html>
+  <body>
+    <h1>Title</h1>
+    <p>
+      Hello,
+      <b>world</b>
+      ! a &lt; b
+    </p>
+    <br>
+    <input value="a &gt; b">
+    <div></div>
+    <pre>  fn main() {
+      println!();
+  }
+</pre>
+    <svg>
+      <path d="M0 0" data-x='a>b'/>
+    </svg>
+  </body>
+</html>"#
+    );
+    assert_eq!(
+        markup::pretty("text", 4).render_to_string().unwrap(),
+        "text"
+    );
+    assert_eq!(markup::pretty("", 4).render_to_string().unwrap(), "");
+}
+
+#[test]
+fn where_clauses_and_const_generics() {
+    markup::define! {
+        Items<I, Title>(items: I, title: Title)
+        where
+            I: IntoIterator + Clone,
+            I::Item: markup::Render,
+            for<'a> &'a Title: markup::Render,
+        {
+            h2 { @title }
+            ul { @for item in items.clone() { li { @item } } }
+        }
+
+        Bytes<const N: usize>(bytes: [u8; N]) {
+            @for byte in bytes.iter() { code { @format!("{:02x}", byte) } }
+            " (" @N " bytes)"
+        }
+    }
+
+    assert_eq!(
+        Items {
+            items: vec!["a", "<b>"],
+            title: String::from("List"),
+        }
+        .to_string(),
+        "<h2>List</h2><ul><li>a</li><li>&lt;b&gt;</li></ul>"
+    );
+    assert_eq!(
+        Bytes {
+            bytes: [0xde, 0xad, 0x01]
+        }
+        .to_string(),
+        "<code>de</code><code>ad</code><code>01</code> (3 bytes)"
+    );
+}
+
+markup::define! {
+    StaticPage {
+        @markup::doctype()
+        div #main.a.b["data-x" = "1 < 2 & \"3\"", title = "Tom's", hidden = true, open = false] {
+            h1 { "Tom & Jerry <3" }
+            p { 42 " " -1.5 " " 'x' " " true }
+            br;
+        }
+    }
+}
+
+#[test]
+fn static_coalescing() {
+    use markup::Render;
+
+    let mut writer = CountingWrites::default();
+    StaticPage {}.render(&mut writer).unwrap();
+    // The doctype is an expression, everything after it is a single literal.
+    assert_eq!(writer.writes, 2);
+
+    let mut writer = CountingWrites::default();
+    markup::new!(div.a { "<b>" span["x" = "y"] {} 1 })
+        .render(&mut writer)
+        .unwrap();
+    assert_eq!(writer.writes, 1);
+    assert_eq!(
+        writer.output,
+        r#"<div class="a">&lt;b&gt;<span x="y"></span>1</div>"#
+    );
+
+    // Literals escaped during expansion match the same values rendered at runtime.
+    let (attribute, apostrophe, text) = ("1 < 2 & \"3\"", "Tom's", "Tom & Jerry <3");
+    let (number, float, char, bool) = (42, -1.5, 'x', true);
+    let (hidden, open) = (true, false);
+    let runtime = markup::new! {
+        @markup::doctype()
+        div #main.a.b["data-x" = attribute, title = apostrophe, hidden = hidden, open = open] {
+            h1 { @text }
+            p { @number " " @float " " @char " " @bool }
+            br;
+        }
+    };
+    let mut writer = CountingWrites::default();
+    runtime.render(&mut writer).unwrap();
+    assert!(writer.writes > 2);
+    assert_eq!(StaticPage {}.to_string(), writer.output);
+}
@@ -0,0 +1,95 @@
+#![cfg(feature = "tracing")]
+
+use markup::tracing;
+use std::sync::{Arc, Mutex};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+markup::define! {
+    Page(title: &'static str) {
+        @Header { title }
+        main { @Footer {} }
+    }
+    Header(title: &'static str) {
+        h1 { @title }
+    }
+    Footer {
+        footer {}
+    }
+}
+
+#[derive(Default)]
+struct State {
+    stack: Vec<u64>,
+    // (name, template, parent)
+    spans: Vec<(&'static str, String, Option<u64>)>,
+}
+
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<State>>);
+
+struct Template<'a>(&'a mut String);
+
+impl tracing::field::Visit for Template<'_> {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "template" {
+            *self.0 = format!("{:?}", value);
+        }
+    }
+
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        if field.name() == "template" {
+            *self.0 = value.to_string();
+        }
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+        let mut state = self.0.lock().unwrap();
+        let mut template = String::new();
+        attributes.record(&mut Template(&mut template));
+        let parent = state.stack.last().copied();
+        state
+            .spans
+            .push((attributes.metadata().name(), template, parent));
+        Id::from_u64(state.spans.len() as u64)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event<'_>) {}
+
+    fn enter(&self, id: &Id) {
+        self.0.lock().unwrap().stack.push(id.into_u64());
+    }
+
+    fn exit(&self, _: &Id) {
+        self.0.lock().unwrap().stack.pop();
+    }
+}
+
+#[test]
+fn spans() {
+    let recorder = Recorder::default();
+    let string =
+        tracing::subscriber::with_default(recorder.clone(), || Page { title: "Home" }.to_string());
+    assert_eq!(string, "<h1>Home</h1><main><footer></footer></main>");
+
+    let state = recorder.0.lock().unwrap();
+    assert_eq!(
+        state.spans,
+        [
+            ("markup::render", "Page".to_string(), None),
+            ("markup::render", "Header".to_string(), Some(1)),
+            ("markup::render", "Footer".to_string(), Some(1)),
+        ]
+    );
+    assert!(state.stack.is_empty());
+}
@@ -0,0 +1,9 @@
+// The expected errors come from a recent stable compiler, older and nightly ones word some of them
+// differently.
+#[rustversion::attr(any(before(1.95), nightly), ignore)]
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/fail/*.rs");
+    t.pass("tests/ui/pass/*.rs");
+}
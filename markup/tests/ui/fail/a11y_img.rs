@@ -0,0 +1,11 @@
+#![deny(deprecated)]
+
+markup::define! {
+    #[markup(a11y)]
+    Logo(src: &'static str) {
+        img[src = src];
+        img[src = src, alt = ""];
+    }
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+#![deny(deprecated)]
+
+markup::define! {
+    #[markup(a11y)]
+    Form {
+        input[name = "q"];
+        input[type = "email", name = "email"];
+        input[type = "checkbox", name = "remember"];
+        input #query[name = "q"];
+        input[name = "q", "aria-label" = "Search"];
+        label { "Name" input[name = "name"]; }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+#![deny(deprecated)]
+
+markup::define! {
+    #[markup(a11y)]
+    Links {
+        a[href = "/"] {}
+        a[href = "/", "aria-label" = "Home"] {}
+        a[href = "/"] { "Home" }
+    }
+}
+
+fn main() {}
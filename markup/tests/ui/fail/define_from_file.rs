@@ -0,0 +1,4 @@
+// trybuild compiles this in target/tests/trybuild/markup, hence the path.
+markup::define_from_file!("../../../../markup/tests/templates/malformed.markup");
+
+fn main() {}
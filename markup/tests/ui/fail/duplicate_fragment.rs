@@ -0,0 +1,8 @@
+markup::define! {
+    Page(count: u32) {
+        @fragment counter { span { @count } }
+        @fragment counter { b { @count } }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+markup::define! {
+    Form {
+        label[for = "name"] { "Name" }
+        input #name;
+        div {
+            input[id = "name"];
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+#![deny(deprecated)]
+
+markup::define! {
+    List(items: Vec<u32>) {
+        @for item in items {
+            li #item { @item }
+        }
+    }
+}
+
+fn main() {}
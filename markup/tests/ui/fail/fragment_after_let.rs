@@ -0,0 +1,8 @@
+markup::define! {
+    Total(prices: Vec<u32>) {
+        @let total: u32 = prices.iter().sum();
+        @fragment summary { p { @total } }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+markup::define! {
+    Article(id: u32, cache: markup::cache::MemoCache<u32>) {
+        @cache(*id, cache) {
+            @fragment body { p { @id } }
+        }
+    }
+}
+
+fn main() {}
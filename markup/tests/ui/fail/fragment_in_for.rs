@@ -0,0 +1,11 @@
+markup::define! {
+    List(items: Vec<&'static str>) {
+        ul {
+            @for item in items {
+                @fragment item { li { @item } }
+            }
+        }
+    }
+}
+
+fn main() {}
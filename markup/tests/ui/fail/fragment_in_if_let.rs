@@ -0,0 +1,9 @@
+markup::define! {
+    Profile(name: Option<&'static str>) {
+        @if let Some(name) = name {
+            @fragment greeting { p { "Hello, " @name } }
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+markup::define! {
+    Status(code: u16) {
+        @match code {
+            200 => { "OK" }
+            code => {
+                @fragment error { p { "Error " @code } }
+            }
+        }
+    }
+}
+
+fn main() {}
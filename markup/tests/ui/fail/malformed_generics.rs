@@ -0,0 +1,7 @@
+markup::define! {
+    Greeting<'a, T: >(name: &'a T) where T: , 5: markup::Render {
+        p { @name }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+markup::define! {
+    Header<'a>(title: &'a str, subtitle: &'a str) {
+        h1 { @title }
+        h2 { @subtitle }
+    }
+    Page {
+        @Header { title: "Home" }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+markup::define! {
+    Header<'a>(title: &'a str) {
+        h1 { @title }
+    }
+    Page {
+        @Header { titel: "Home" }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+pub enum Status {
+    Active,
+    Suspended { days: u32 },
+    Deleted,
+}
+
+markup::define! {
+    Badge(status: Status) {
+        @match status {
+            Status::Active => { "active" }
+            Status::Suspended { days } => { "suspended for " @days " days" }
+        }
+    }
+}
+
+fn main() {}
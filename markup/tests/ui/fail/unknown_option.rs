@@ -0,0 +1,6 @@
+markup::define! {
+    #[markup(dyn_writer)]
+    Page { "page" }
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+#![deny(unused_variables)]
+
+markup::define! {
+    Card(title: &'static str, subtitle: &'static str) {
+        h1 { @title }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+#![deny(deprecated)]
+
+markup::define! {
+    #[markup(a11y)]
+    Spread(attrs: Vec<(&'static str, &'static str)>) {
+        img[src = "a.png", ..attrs];
+        a[href = "/", ..attrs] {}
+        input[{concat!("aria-", "label")} = "Search"];
+        @if true {
+            img[src = "a.png", alt = "A"];
+        }
+    }
+    // Not checked without the option.
+    Unchecked {
+        img[src = "a.png"];
+    }
+}
+
+fn main() {}
@@ -0,0 +1,27 @@
+#![deny(deprecated)]
+
+markup::define! {
+    List(items: Vec<u32>) {
+        @for item in items {
+            li #{format!("item-{}", item)} { @item }
+        }
+        @if items.is_empty() {
+            p #empty { "Nothing here" }
+        } else {
+            p #empty { "Total: " @items.len() }
+        }
+        @Item { id: "first" }
+        @Item { id: "first" }
+    }
+    Item(id: &'static str) {
+        div[id = id] {}
+    }
+}
+
+fn main() {
+    let list = List { items: vec![1, 2] };
+    assert_eq!(
+        list.to_string(),
+        r#"<li id="item-1">1</li><li id="item-2">2</li><p id="empty">Total: 2</p><div id="first"></div><div id="first"></div>"#
+    );
+}
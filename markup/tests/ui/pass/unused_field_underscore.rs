@@ -0,0 +1,15 @@
+#![deny(unused_variables)]
+
+markup::define! {
+    Card(title: &'static str, _subtitle: &'static str) {
+        h1 { @title }
+    }
+}
+
+fn main() {
+    let card = Card {
+        title: "Title",
+        _subtitle: "Subtitle",
+    };
+    assert_eq!(card.to_string(), "<h1>Title</h1>");
+}
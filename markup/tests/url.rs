@@ -0,0 +1,10 @@
+#![cfg(feature = "url")]
+
+#[test]
+fn url() {
+    let url = url::Url::parse("https://example.com/a b?x=1&y=<2>").unwrap();
+    assert_eq!(
+        markup::new!(a[href = markup::href(&url)] {}).to_string(),
+        r#"<a href="https://example.com/a%20b?x=1&amp;y=%3C2%3E"></a>"#
+    );
+}